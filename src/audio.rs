@@ -2,7 +2,6 @@ use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::Stream;
 use cpal::{SampleFormat, SampleRate, SupportedStreamConfig};
 use std::sync::{Arc, Mutex};
-use usfx::{Mixer, Sample};
 
 #[cfg(not(target_os = "windows"))]
 pub const SAMPLE_RATE: u32 = 44_100;
@@ -10,13 +9,16 @@ pub const SAMPLE_RATE: u32 = 44_100;
 pub const SAMPLE_RATE: u32 = 48_000;
 
 pub struct Audio {
-    mixer: Arc<Mutex<Mixer>>,
-    stream: Stream,
+    player: Arc<Mutex<Player>>,
+    /// None if muted
+    stream: Option<Stream>,
+    /// Stream errors since the last call to [take_errors](Audio::take_errors)
+    errors: Arc<Mutex<Vec<String>>>,
 }
 
 impl Audio {
     pub fn new() -> Self {
-        let mixer = Arc::new(Mutex::new(Mixer::new(SAMPLE_RATE as usize)));
+        let player = Arc::new(Mutex::new(Player::default()));
         let host = cpal::default_host();
 
         let device = host
@@ -47,29 +49,79 @@ impl Audio {
             SampleFormat::F32,
         );
 
-        let stream_mixer = mixer.clone();
+        let stream_player = player.clone();
+        let errors = Arc::new(Mutex::new(vec![]));
+        let stream_errors = errors.clone();
+        let channels = format.channels() as usize;
 
         let stream = device
             .build_output_stream::<f32, _, _>(
                 &format.config(),
-                move |data, _| stream_mixer.lock().unwrap().generate(data),
-                |err| eprintln!("cpal error: {:?}", err),
+                move |data, _| stream_player.lock().unwrap().generate(data, channels),
+                move |err| {
+                    eprintln!("cpal error: {:?}", err);
+                    stream_errors.lock().unwrap().push(err.to_string());
+                },
                 None,
             )
             .expect("could not build output stream");
 
-        let struct_mixer = mixer;
         Self {
-            mixer: struct_mixer,
-            stream,
+            player,
+            stream: Some(stream),
+            errors,
         }
     }
 
-    pub fn play(&mut self, sample: Sample) {
-        self.mixer.lock().unwrap().play(sample);
+    /// Audio that doesn't open an output device and ignores anything played, for previews
+    pub fn muted() -> Self {
+        Self {
+            player: Arc::new(Mutex::new(Player::default())),
+            stream: None,
+            errors: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    /// Play already rendered mono audio, see [render](crate::render::render)
+    pub fn play(&mut self, data: Vec<f32>) {
+        if self.stream.is_some() {
+            self.player.lock().unwrap().voices.push((data, 0));
+        }
     }
 
     pub fn run(&mut self) {
-        self.stream.play().expect("unable to start stream");
+        if let Some(stream) = &self.stream {
+            if let Err(err) = stream.play() {
+                eprintln!("unable to start stream: {err:?}");
+                self.errors.lock().unwrap().push(err.to_string());
+            }
+        }
+    }
+
+    pub fn take_errors(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.errors.lock().unwrap())
+    }
+}
+
+/// Mixes rendered sounds into the output stream
+#[derive(Debug, Default)]
+struct Player {
+    /// Rendered data and play position
+    voices: Vec<(Vec<f32>, usize)>,
+}
+
+impl Player {
+    fn generate(&mut self, output: &mut [f32], channels: usize) {
+        for frame in output.chunks_mut(channels.max(1)) {
+            let mut value = 0.0;
+            for (data, pos) in self.voices.iter_mut() {
+                if let Some(sample) = data.get(*pos) {
+                    value += sample;
+                    *pos += 1;
+                }
+            }
+            frame.iter_mut().for_each(|out| *out = value);
+        }
+        self.voices.retain(|(data, pos)| *pos < data.len());
     }
 }
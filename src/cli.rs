@@ -0,0 +1,274 @@
+use crate::export::{export_all, export_sprite, file_stem, ExportFormat};
+use crate::instrument::export_instrument;
+use crate::meter::NormaliseTarget;
+use crate::settings::Settings;
+use crate::variation::{create_variants, export_variants};
+use anyhow::{anyhow, Context, Result};
+use pixels_graphics_lib::prelude::*;
+
+const USAGE: &str = "expected one of
+  --export-all [dir] [--format fmt] [--rate hz]
+  --export-sprite [dir] [--format fmt] [--rate hz] [--gap secs] [--slots 0,1,2]
+  --export-instrument [dir] [--rate hz] [--slots 0,1,2]
+  --export-variations [dir] [--format fmt] [--rate hz] [--slot 0] [--count n] [--seed n]
+      [--jitter volume=0.1,freq=0.05]
+  --normalise [--peak db | --loudness lufs]
+all commands also take [--max-length secs] to limit how long sounds can be
+formats are wav16, wav8, aiff, s16le and f32le";
+
+const COMMANDS: [&str; 5] = [
+    "--export-all",
+    "--export-sprite",
+    "--export-instrument",
+    "--export-variations",
+    "--normalise",
+];
+
+/// Arguments starting with `--` are commands, anything else (such as arguments added by a
+/// launcher) is ignored and the window opens as normal
+pub fn is_command(arg: &str) -> bool {
+    arg.starts_with("--")
+}
+
+/// Run a command from the command line instead of opening the window
+///
+/// `dir`, the format and rate are remembered for later exports, if they're missing the last used
+/// values are used.
+/// Slots are indexes into [Settings::saved], all slots are used if none are given.
+/// The variation count, seed and jitter are also remembered
+pub fn run(command: &str, args: Vec<String>) -> Result<()> {
+    attach_console();
+    // checked before anything is changed so a mistake doesn't alter the saved settings
+    if !COMMANDS.contains(&command) {
+        return Err(anyhow!("Unknown command {command}, {USAGE}"));
+    }
+    let options = Args::parse(args)?;
+    let mut app_prefs: AppPrefs<Settings> =
+        AppPrefs::new("app", "emmabritton", "usfx_tester", Settings::default)?;
+    if let Some(dir) = &options.dir {
+        app_prefs.data.export_dir = Some(dir.into());
+    }
+    if let Some(format) = options.format {
+        app_prefs.data.export.format = format;
+    }
+    if let Some(rate) = options.rate {
+        app_prefs.data.export.sample_rate = rate;
+    }
+    if let Some(gap) = options.gap {
+        app_prefs.data.sprite_gap = gap;
+    }
+    if let Some(count) = options.count {
+        app_prefs.data.variation.count = count;
+    }
+    if let Some(seed) = options.seed {
+        app_prefs.data.variation.seed = seed;
+    }
+    for (name, range) in &options.jitter {
+        app_prefs.data.variation.jitter.set(name, *range)?;
+    }
+    if let Some(max_length) = options.max_length {
+        app_prefs.data.max_length = max_length;
+    }
+    if let Some(target) = options.target {
+        app_prefs.data.normalise_target = target;
+    }
+    if command == "--normalise" {
+        let target = app_prefs.data.normalise_target;
+        let max_length = app_prefs.data.max_length;
+        for (slot, sound) in app_prefs.data.saved.iter_mut().enumerate() {
+            if let Some(sound) = sound {
+                sound.normalise(target, max_length);
+                println!("{slot} volume {:.2}", sound.volume);
+            }
+        }
+        app_prefs.save();
+        return Ok(());
+    }
+    app_prefs.save();
+    let settings = &app_prefs.data;
+    let dir = settings.export_dir();
+    match command {
+        "--export-all" => {
+            for entry in export_all(&settings.saved, &settings.export, settings.max_length, &dir)? {
+                println!("{} {}", entry.slot, dir.join(entry.file).display());
+            }
+        }
+        "--export-sprite" => {
+            let slots = options
+                .slots
+                .unwrap_or_else(|| (0..settings.saved.len()).collect());
+            let path = export_sprite(
+                &settings.saved,
+                &slots,
+                settings.sprite_gap,
+                &settings.export,
+                settings.max_length,
+                &dir,
+            )?;
+            println!("{}", path.display());
+        }
+        "--export-instrument" => {
+            let slots = options
+                .slots
+                .unwrap_or_else(|| (0..settings.saved.len()).collect());
+            for slot in slots {
+                if let Some(Some(sound)) = settings.saved.get(slot) {
+                    let name = file_stem(slot, sound);
+                    let path = export_instrument(
+                        sound,
+                        &name,
+                        settings.export.sample_rate,
+                        settings.max_length,
+                        &dir,
+                    )?;
+                    println!("{slot} {}", path.display());
+                }
+            }
+        }
+        "--export-variations" => {
+            let slot = options.slot.unwrap_or_default();
+            let sound = settings
+                .saved
+                .get(slot)
+                .and_then(|sound| sound.as_ref())
+                .with_context(|| format!("Slot {slot} is empty"))?;
+            let variants = create_variants(sound, &settings.variation);
+            let path = export_variants(
+                sound,
+                &variants,
+                &settings.variation,
+                &settings.export,
+                settings.max_length,
+                &dir,
+            )?;
+            println!("{}", path.display());
+        }
+        _ => return Err(anyhow!("Unknown command {command}, {USAGE}")),
+    }
+    Ok(())
+}
+
+/// The app is built as a Windows GUI program, so it has no console for output and errors unless
+/// it's attached to the one it was started from
+#[cfg(windows)]
+fn attach_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    // fails if there's no parent console (such as when started from Explorer), there's nowhere to
+    // show output then anyway
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+#[derive(Debug, Default)]
+struct Args {
+    dir: Option<String>,
+    gap: Option<f32>,
+    format: Option<ExportFormat>,
+    rate: Option<u32>,
+    slots: Option<Vec<usize>>,
+    slot: Option<usize>,
+    count: Option<usize>,
+    seed: Option<u64>,
+    jitter: Vec<(String, f32)>,
+    target: Option<NormaliseTarget>,
+    max_length: Option<f32>,
+}
+
+impl Args {
+    fn parse(args: Vec<String>) -> Result<Self> {
+        let mut options = Args::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .with_context(|| format!("Missing value for {arg}"))
+            };
+            match arg.as_str() {
+                "--gap" => options.gap = Some(value()?.parse().context("Invalid gap")?),
+                "--format" => {
+                    let format: ExportFormat = value()?.parse()?;
+                    if format.is_source() {
+                        return Err(anyhow!(
+                            "{} can only be used for the current sound, from the app",
+                            format.name()
+                        ));
+                    }
+                    options.format = Some(format)
+                }
+                "--rate" => {
+                    let rate = value()?.parse().context("Invalid rate")?;
+                    if rate == 0 {
+                        return Err(anyhow!("Invalid rate"));
+                    }
+                    options.rate = Some(rate)
+                }
+                "--slot" => options.slot = Some(value()?.parse().context("Invalid slot")?),
+                "--count" => options.count = Some(value()?.parse().context("Invalid count")?),
+                "--seed" => options.seed = Some(value()?.parse().context("Invalid seed")?),
+                "--jitter" => {
+                    for pair in value()?.split(',') {
+                        let (name, range) = pair
+                            .split_once('=')
+                            .with_context(|| format!("Invalid jitter {pair}"))?;
+                        let range = range.parse().context("Invalid jitter")?;
+                        options.jitter.push((name.trim().to_string(), range));
+                    }
+                }
+                "--peak" => {
+                    let level = value()?.parse().context("Invalid peak")?;
+                    options.target = Some(NormaliseTarget::Peak(level))
+                }
+                "--loudness" => {
+                    let level = value()?.parse().context("Invalid loudness")?;
+                    options.target = Some(NormaliseTarget::Loudness(level))
+                }
+                "--max-length" => {
+                    options.max_length = Some(value()?.parse().context("Invalid max length")?)
+                }
+                "--slots" => {
+                    options.slots = Some(
+                        value()?
+                            .split(',')
+                            .map(|slot| slot.trim().parse())
+                            .collect::<Result<_, _>>()
+                            .context("Invalid slots")?,
+                    )
+                }
+                _ if !arg.starts_with("--") && options.dir.is_none() => options.dir = Some(arg),
+                _ => return Err(anyhow!("Unexpected argument {arg}, {USAGE}")),
+            }
+        }
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn only_dashed_arguments_are_commands() {
+        assert!(is_command("--export-all"));
+        assert!(is_command("--export-al"));
+        assert!(!is_command("-psn_0_12345"));
+        assert!(!is_command("sounds.json"));
+    }
+
+    #[test]
+    fn rejects_unknown_command() {
+        let args = vec![
+            String::from("out"),
+            String::from("--format"),
+            String::from("wav8"),
+        ];
+        let err = run("--export-al", args).unwrap_err();
+        assert!(err.to_string().starts_with("Unknown command --export-al"));
+    }
+}
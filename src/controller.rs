@@ -1,3 +1,8 @@
+use crate::export::ExportOptions;
+use crate::keymap::{Action, KeyMap};
+use crate::meter::{Levels, NormaliseTarget};
+use crate::notes::{Tuning, NOTE_NAMES, SCALE_NAMES};
+use crate::render::DEFAULT_MAX_LENGTH;
 use crate::settings::SoundSave;
 use crate::theme::Theme;
 use crate::ui::*;
@@ -6,7 +11,7 @@ use crate::Audio;
 use indexmap::{indexmap, IndexMap};
 use pixels_graphics_lib::buffer_graphics_lib::prelude::*;
 use pixels_graphics_lib::prelude::*;
-use usfx::{DutyCycle, OscillatorType, Sample};
+use usfx::{DutyCycle, OscillatorType};
 
 pub struct Controller {
     pub items: IndexMap<Item, State>,
@@ -20,22 +25,73 @@ pub struct Controller {
     pub osc_text: IndexMap<OscillatorType, Text>,
     pub waveform: Waveform,
     pub has_changed: bool,
+    pub page: Page,
+    pub input_mode: InputMode,
+    pub octave: i32,
+    pub registers: [Option<SoundSave>; REGISTER_COUNT],
+    pub active_register: usize,
+    /// Register the active one is compared against, the same as active if there's nothing to compare
+    pub compare_register: usize,
+    pub compare_waveform: Option<Waveform>,
+    /// Parameters that differ between the active and compared register
+    pub differences: Vec<&'static str>,
+    pub levels: Levels,
+    /// Draw the waveform on a fixed ±1.0 scale
+    pub absolute_waveform: bool,
+    /// Longest sound to render, in seconds
+    pub max_length: f32,
+    /// The waveform is out of date and a new one is being rendered
+    pub rendering: bool,
+    /// Seconds left to show the theme name for
+    pub theme_name_time: f64,
+    pub keys: KeyMap,
+    /// Name and notes of the current sound, copied into saves
+    pub name: String,
+    pub notes: String,
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum InputMode {
+    /// Letter keys change parameters
+    Parameters,
+    /// Letter keys play the sound at different notes
+    Keyboard,
+}
+
+pub const REGISTER_COUNT: usize = 4;
+const MIN_OCTAVE: i32 = 0;
+const MAX_OCTAVE: i32 = 7;
+/// Seconds to show the theme name for after changing theme
+const THEME_NAME_TIME: f64 = 2.0;
+
 impl Controller {
     pub fn new(audio: Audio, theme: &Theme) -> Self {
         let items = indexmap! {
-            Item::new('Q', 'W', KeyCode::KeyQ, KeyCode::KeyW, ITEM_VOLUME) => State::Enabled(1.0),
-            Item::new('A', 'S', KeyCode::KeyA, KeyCode::KeyS, ITEM_ATTACK)=> State::Enabled(0.1),
-            Item::new('Z', 'X', KeyCode::KeyZ, KeyCode::KeyX, ITEM_DECAY)=> State::Enabled(0.1),
-            Item::new('E', 'R', KeyCode::KeyE, KeyCode::KeyR, ITEM_SUSTAIN)=> State::Enabled(0.5),
-            Item::new('D', 'F', KeyCode::KeyD, KeyCode::KeyF, ITEM_RELEASE)=> State::Enabled(0.5),
-            Item::new_int('C', 'V', KeyCode::KeyC, KeyCode::KeyV, ITEM_FREQ)=> State::Enabled(500.0),
-            Item::new_tog('T', 'Y', 'U', KeyCode::KeyT, KeyCode::KeyY,KeyCode::KeyU, ITEM_CRUNCH)=> State::Disabled(0.0),
-            Item::new_tog('G', 'H', 'J', KeyCode::KeyG, KeyCode::KeyH,KeyCode::KeyJ, ITEM_DRIVE)=> State::Disabled(0.0),
+            Item::new(ITEM_VOLUME) => State::Enabled(1.0),
+            Item::new(ITEM_ATTACK)=> State::Enabled(0.1),
+            Item::new(ITEM_DECAY)=> State::Enabled(0.1),
+            Item::new(ITEM_SUSTAIN)=> State::Enabled(0.5),
+            Item::new(ITEM_RELEASE)=> State::Enabled(0.5),
+            Item::new_int(ITEM_FREQ).with_type(ItemType::Pitch)=> State::Enabled(500.0),
+            Item::new_tog(ITEM_CRUNCH)=> State::Disabled(0.0),
+            Item::new_tog(ITEM_DRIVE)=> State::Disabled(0.0),
+            Item::new_tog(ITEM_VIBRATO).on_page(Page::Modulation)=> State::Disabled(0.2),
+            Item::new(ITEM_VIB_RATE).on_page(Page::Modulation)=> State::Enabled(6.0),
+            Item::new(ITEM_VIB_DELAY).on_page(Page::Modulation)=> State::Enabled(0.0),
+            Item::new_tog(ITEM_TREMOLO).on_page(Page::Modulation)=> State::Disabled(0.5),
+            Item::new(ITEM_TREM_RATE).on_page(Page::Modulation)=> State::Enabled(8.0),
+            Item::new(ITEM_TREM_DELAY).on_page(Page::Modulation)=> State::Enabled(0.0),
+            Item::new_tog(ITEM_ARPEGGIO).on_page(Page::Sequence)=> State::Disabled(2.0),
+            Item::new(ITEM_ARP_TIME).on_page(Page::Sequence)=> State::Enabled(0.1),
+            Item::new_tog(ITEM_REPEAT).on_page(Page::Sequence)=> State::Disabled(0.1),
+            Item::new_tog(ITEM_SNAP).on_page(Page::Tuning).with_type(ItemType::Choice(SCALE_NAMES))=> State::Disabled(0.0),
+            Item::new(ITEM_ROOT).on_page(Page::Tuning).with_type(ItemType::Choice(NOTE_NAMES))=> State::Enabled(0.0),
+            Item::new_int(ITEM_TUNING).on_page(Page::Tuning)=> State::Enabled(440.0),
         };
 
-        let (shapes, texts, osc_text, duty_text, button_shape) = Controller::gen_themed(theme);
+        let keys = KeyMap::default();
+        let (shapes, texts, osc_text, duty_text, button_shape) =
+            Controller::gen_themed(theme, &keys);
 
         Controller {
             items,
@@ -47,8 +103,24 @@ impl Controller {
             texts,
             duty_text,
             osc_text,
-            waveform: Waveform::new(vec![], 1, 1, 1),
+            waveform: Waveform::new(vec![], 1, 1, 1, false),
             has_changed: true,
+            page: Page::Main,
+            input_mode: InputMode::Parameters,
+            octave: 4,
+            registers: [None, None, None, None],
+            active_register: 0,
+            compare_register: 0,
+            compare_waveform: None,
+            differences: vec![],
+            levels: Levels::default(),
+            absolute_waveform: false,
+            max_length: DEFAULT_MAX_LENGTH,
+            rendering: false,
+            theme_name_time: 0.0,
+            keys,
+            name: String::new(),
+            notes: String::new(),
         }
     }
 }
@@ -57,6 +129,7 @@ impl Controller {
     #[allow(clippy::type_complexity)]
     pub fn gen_themed(
         theme: &Theme,
+        keys: &KeyMap,
     ) -> (
         ShapeCollection,
         Vec<Text>,
@@ -66,7 +139,7 @@ impl Controller {
     ) {
         let shapes = generate_shapes(theme);
 
-        let texts = generate_text(theme);
+        let texts = generate_text(theme, keys);
         let osc_text = osc_text(theme);
         let duty_text = duty_text(theme);
 
@@ -76,7 +149,18 @@ impl Controller {
     }
 
     pub fn on_theme_change(&mut self, theme: &Theme) {
-        let (shapes, texts, osc_text, duty_text, button_shape) = Controller::gen_themed(theme);
+        self.regenerate(theme);
+        self.theme_name_time = THEME_NAME_TIME;
+    }
+
+    pub fn set_keys(&mut self, keys: KeyMap, theme: &Theme) {
+        self.keys = keys;
+        self.regenerate(theme);
+    }
+
+    fn regenerate(&mut self, theme: &Theme) {
+        let (shapes, texts, osc_text, duty_text, button_shape) =
+            Controller::gen_themed(theme, &self.keys);
         self.shapes = shapes;
         self.texts = texts;
         self.osc_text = osc_text;
@@ -85,88 +169,189 @@ impl Controller {
     }
 
     pub fn key_pressed(&mut self, key: KeyCode, shift_pressed: bool, ctrl_pressed: bool) {
-        for (item, value) in self.items.iter_mut() {
-            let mut delta = match item.item_type {
-                ItemType::Float => 0.1,
-                ItemType::Int => 10.0,
+        let actions = self.keys.actions(key);
+        if actions.contains(&Action::WaveformMode) {
+            self.absolute_waveform = !self.absolute_waveform;
+            self.compare_waveform = None;
+            self.has_changed = true;
+            return;
+        }
+        if actions.contains(&Action::PrevRegister) {
+            self.select_register(self.active_register.saturating_sub(1));
+            return;
+        }
+        if actions.contains(&Action::NextRegister) {
+            self.select_register((self.active_register + 1).min(REGISTER_COUNT - 1));
+            return;
+        }
+        if actions.contains(&Action::KeyboardMode) {
+            self.input_mode = match self.input_mode {
+                InputMode::Parameters => InputMode::Keyboard,
+                InputMode::Keyboard => InputMode::Parameters,
             };
-            if shift_pressed {
-                delta *= 10.0;
+            return;
+        }
+        if self.input_mode == InputMode::Keyboard {
+            self.keyboard_key_pressed(key, &actions, shift_pressed);
+            return;
+        }
+        if actions.contains(&Action::NextPage) {
+            self.page = self.page.next();
+            return;
+        }
+        let tuning = self.tuning();
+        for (item, value) in self.items.iter_mut() {
+            if item.page != self.page {
+                continue;
             }
-            if ctrl_pressed {
-                delta /= 10.0;
+            let mut delta = item.item_type.step();
+            if !matches!(item.item_type, ItemType::Choice(_)) {
+                if shift_pressed {
+                    delta *= 10.0;
+                }
+                if ctrl_pressed {
+                    delta /= 10.0;
+                }
             }
-            if key == item.dec_code {
+            if actions.contains(&Action::Decrease(item.name)) {
                 self.has_changed = true;
-                if value.num() > delta {
-                    *value = value.update(-delta);
-                } else {
-                    *value = value.replace(0.0);
-                }
+                *value = match item.item_type {
+                    ItemType::Pitch if tuning.scale.is_some() => {
+                        value.replace(tuning.step(value.num(), -1, shift_pressed).round())
+                    }
+                    _ if value.num() > delta => value.update(-delta),
+                    _ => value.replace(0.0),
+                };
+            }
+            if actions.contains(&Action::Increase(item.name)) {
+                self.has_changed = true;
+                *value = match item.item_type {
+                    ItemType::Pitch if tuning.scale.is_some() => {
+                        value.replace(tuning.step(value.num(), 1, shift_pressed).round())
+                    }
+                    ItemType::Choice(choices) => {
+                        value.replace((value.num() + 1.0).min((choices.len() - 1) as f32))
+                    }
+                    _ => value.update(delta),
+                };
             }
-            if key == item.inc_code {
+            if item.toggle && actions.contains(&Action::Toggle(item.name)) {
                 self.has_changed = true;
-                *value = value.update(delta);
+                *value = value.swap();
+            }
+        }
+        let tuning = self.tuning();
+        if self.has_changed && tuning.scale.is_some() {
+            for (item, value) in self.items.iter_mut() {
+                if item.item_type == ItemType::Pitch {
+                    *value = value.replace(tuning.snap(value.num()).round());
+                }
             }
-            if let Some(tog) = item.toggle_code {
-                if key == tog {
+        }
+        for action in actions {
+            match action {
+                Action::Oscillator(osc) => {
+                    self.has_changed = true;
+                    self.osc_type = osc;
+                }
+                Action::Duty(cycle) => {
                     self.has_changed = true;
-                    *value = value.swap();
+                    self.cycle = cycle;
+                }
+                Action::Play => {
+                    let data = self.create_data();
+                    self.audio.play(data);
                 }
+                _ => {}
             }
         }
-        if key == KeyCode::KeyI {
-            self.has_changed = true;
-            self.osc_type = OscillatorType::Sine;
-        } else if key == KeyCode::KeyO {
-            self.has_changed = true;
-            self.osc_type = OscillatorType::Triangle;
-        } else if key == KeyCode::KeyP {
-            self.has_changed = true;
-            self.osc_type = OscillatorType::Saw;
-        } else if key == KeyCode::KeyK {
-            self.has_changed = true;
-            self.osc_type = OscillatorType::Square;
-        } else if key == KeyCode::KeyL {
-            self.has_changed = true;
-            self.osc_type = OscillatorType::Noise;
+    }
+
+    /// The two rows cover C to the E above the next C, holding shift plays them an octave higher
+    /// to reach the rest of the second octave
+    ///
+    /// The piano keys are fixed as they're laid out like a keyboard
+    fn keyboard_key_pressed(&mut self, key: KeyCode, actions: &[Action], shift_pressed: bool) {
+        if actions.contains(&Action::Play) {
+            let data = self.create_data();
+            self.audio.play(data);
+            return;
         }
-        if key == KeyCode::KeyB {
-            self.has_changed = true;
-            self.cycle = DutyCycle::Half;
-        } else if key == KeyCode::KeyN {
-            self.has_changed = true;
-            self.cycle = DutyCycle::Third;
-        } else if key == KeyCode::KeyM {
-            self.has_changed = true;
-            self.cycle = DutyCycle::Quarter;
-        } else if key == KeyCode::Comma {
-            self.has_changed = true;
-            self.cycle = DutyCycle::Eight;
+        match key {
+            KeyCode::ArrowUp => self.octave = (self.octave + 1).min(MAX_OCTAVE),
+            KeyCode::ArrowDown => self.octave = (self.octave - 1).max(MIN_OCTAVE),
+            _ => {
+                if let Some(semitone) = piano_key(key) {
+                    let mut note = (self.octave + 1) * 12 + semitone;
+                    if shift_pressed {
+                        note += 12;
+                    }
+                    let freq = self.tuning().note_freq(note).round() as usize;
+                    let data = self.create_data_at(freq);
+                    self.audio.play(data);
+                }
+            }
         }
-        if key == KeyCode::Space {
-            let sample = self.create_sample();
-            self.audio.play(sample);
+    }
+
+    /// Store the current sound in the active register and switch to `idx`, copying the current
+    /// sound into it if it's empty
+    fn select_register(&mut self, idx: usize) {
+        if idx == self.active_register {
+            return;
+        }
+        self.registers[self.active_register] = Some(self.create_save_data());
+        self.compare_register = self.active_register;
+        self.active_register = idx;
+        match self.registers[idx].clone() {
+            Some(sound) => self.load(&sound),
+            None => self.registers[idx] = Some(self.create_save_data()),
+        }
+        self.compare_waveform = None;
+        self.has_changed = true;
+        let data = self.create_data();
+        self.audio.play(data);
+    }
+
+    /// Change the volume so the current sound reaches `target`
+    pub fn normalise(&mut self, target: NormaliseTarget) {
+        let mut sound = self.create_save_data();
+        sound.normalise(target, self.max_length);
+        for (item, value) in self.items.iter_mut() {
+            if item.name == ITEM_VOLUME {
+                *value = value.replace(sound.volume);
+            }
+        }
+        self.has_changed = true;
+    }
+
+    pub fn compare_sound(&self) -> Option<&SoundSave> {
+        if self.compare_register == self.active_register {
+            None
+        } else {
+            self.registers[self.compare_register].as_ref()
         }
     }
 
     pub fn load(&mut self, sound: &SoundSave) {
+        self.name = sound.name.clone();
+        self.notes = sound.notes.clone();
         for (item, value) in self.items.iter_mut() {
             match item.name {
                 ITEM_VOLUME => {
-                    value.replace(sound.volume);
+                    *value = value.replace(sound.volume);
                 }
                 ITEM_ATTACK => {
-                    value.replace(sound.attack);
+                    *value = value.replace(sound.attack);
                 }
                 ITEM_DECAY => {
-                    value.replace(sound.decay);
+                    *value = value.replace(sound.decay);
                 }
                 ITEM_SUSTAIN => {
-                    value.replace(sound.sustain);
+                    *value = value.replace(sound.sustain);
                 }
                 ITEM_RELEASE => {
-                    value.replace(sound.release);
+                    *value = value.replace(sound.release);
                 }
                 ITEM_CRUNCH => {
                     *value = if sound.crunch_enabled {
@@ -183,12 +368,71 @@ impl Controller {
                     };
                 }
                 ITEM_FREQ => {
-                    value.replace(sound.freq() as f32);
+                    *value = value.replace(sound.freq() as f32);
+                }
+                ITEM_VIBRATO => {
+                    *value = if sound.vibrato_enabled {
+                        State::Enabled(sound.vibrato)
+                    } else {
+                        State::Disabled(sound.vibrato)
+                    };
+                }
+                ITEM_VIB_RATE => {
+                    *value = value.replace(sound.vibrato_rate);
+                }
+                ITEM_VIB_DELAY => {
+                    *value = value.replace(sound.vibrato_delay);
+                }
+                ITEM_TREMOLO => {
+                    *value = if sound.tremolo_enabled {
+                        State::Enabled(sound.tremolo)
+                    } else {
+                        State::Disabled(sound.tremolo)
+                    };
+                }
+                ITEM_TREM_RATE => {
+                    *value = value.replace(sound.tremolo_rate);
+                }
+                ITEM_TREM_DELAY => {
+                    *value = value.replace(sound.tremolo_delay);
+                }
+                ITEM_ARPEGGIO => {
+                    *value = if sound.arpeggio_enabled {
+                        State::Enabled(sound.arpeggio)
+                    } else {
+                        State::Disabled(sound.arpeggio)
+                    };
+                }
+                ITEM_ARP_TIME => {
+                    *value = value.replace(sound.arpeggio_time);
+                }
+                ITEM_REPEAT => {
+                    *value = if sound.repeat_enabled {
+                        State::Enabled(sound.repeat)
+                    } else {
+                        State::Disabled(sound.repeat)
+                    };
+                }
+                ITEM_SNAP => {
+                    let scale = sound.snap_scale.min(SCALE_NAMES.len() - 1) as f32;
+                    *value = if sound.snap_enabled {
+                        State::Enabled(scale)
+                    } else {
+                        State::Disabled(scale)
+                    };
+                }
+                ITEM_ROOT => {
+                    *value = value.replace(sound.snap_root.min(NOTE_NAMES.len() - 1) as f32);
+                }
+                ITEM_TUNING => {
+                    *value = value.replace(sound.tuning);
                 }
                 _ => {}
             }
             self.has_changed = true;
         }
+        self.osc_type = sound.osc;
+        self.cycle = sound.duty;
     }
 
     pub fn create_save_data(&self) -> SoundSave {
@@ -209,66 +453,120 @@ impl Controller {
                     save.drive_enabled = matches!(value, State::Enabled(_));
                 }
                 ITEM_FREQ => save.freq = value.num() as u64,
+                ITEM_VIBRATO => {
+                    save.vibrato = value.num();
+                    save.vibrato_enabled = matches!(value, State::Enabled(_));
+                }
+                ITEM_VIB_RATE => save.vibrato_rate = value.num(),
+                ITEM_VIB_DELAY => save.vibrato_delay = value.num(),
+                ITEM_TREMOLO => {
+                    save.tremolo = value.num();
+                    save.tremolo_enabled = matches!(value, State::Enabled(_));
+                }
+                ITEM_TREM_RATE => save.tremolo_rate = value.num(),
+                ITEM_TREM_DELAY => save.tremolo_delay = value.num(),
+                ITEM_ARPEGGIO => {
+                    save.arpeggio = value.num();
+                    save.arpeggio_enabled = matches!(value, State::Enabled(_));
+                }
+                ITEM_ARP_TIME => save.arpeggio_time = value.num(),
+                ITEM_REPEAT => {
+                    save.repeat = value.num();
+                    save.repeat_enabled = matches!(value, State::Enabled(_));
+                }
+                ITEM_SNAP => {
+                    save.snap_scale = value.num() as usize;
+                    save.snap_enabled = matches!(value, State::Enabled(_));
+                }
+                ITEM_ROOT => save.snap_root = value.num() as usize,
+                ITEM_TUNING => save.tuning = value.num(),
                 _ => {}
             }
         }
         save.osc = self.osc_type;
         save.duty = self.cycle;
-        save.fix_name();
+        save.name = self.name.clone();
+        save.notes = self.notes.clone();
         save
     }
 
-    pub fn create_sample(&self) -> Sample {
-        let mut sample = Sample::default();
-        sample.osc_type(self.osc_type);
-        sample.osc_duty_cycle(self.cycle);
+    /// Read from the items directly as it's used every frame
+    pub fn tuning(&self) -> Tuning {
+        let mut tuning = Tuning::default();
         for (item, value) in &self.items {
             match item.name {
-                ITEM_VOLUME => {
-                    sample.volume(value.num());
-                }
-                ITEM_DECAY => {
-                    sample.env_decay(value.num());
-                }
-                ITEM_SUSTAIN => {
-                    sample.env_sustain(value.num());
-                }
-                ITEM_ATTACK => {
-                    sample.env_attack(value.num());
-                }
-                ITEM_RELEASE => {
-                    sample.env_release(value.num());
-                }
-                ITEM_CRUNCH => {
-                    if let State::Enabled(value) = value {
-                        sample.dis_crunch(*value);
+                ITEM_SNAP => {
+                    if let State::Enabled(scale) = value {
+                        tuning.scale = Some(*scale as usize);
                     }
                 }
-                ITEM_DRIVE => {
-                    if let State::Enabled(value) = value {
-                        sample.dis_drive(*value);
-                    }
-                }
-                ITEM_FREQ => {
-                    sample.osc_frequency(value.num() as usize);
-                }
+                ITEM_ROOT => tuning.root = value.num() as i32,
+                ITEM_TUNING => tuning.a4 = value.num().max(1.0),
                 _ => {}
             }
         }
-        sample
+        tuning
+    }
+
+    /// Clear the waveform and levels after rendering failed, so those of the previous sound
+    /// aren't shown as if they were current
+    pub fn render_failed(&mut self) {
+        self.waveform = Waveform::new(vec![], 1, 1, 1, false);
+        self.compare_waveform = None;
+        self.levels = Levels::default();
+        self.rendering = false;
+    }
+
+    /// Render the current sound with the sequence and modulation applied
+    pub fn create_data(&self) -> Vec<f32> {
+        self.create_save_data().create_data(self.max_length)
+    }
+
+    /// Render the current sound at `freq` hz instead of the Freq value
+    pub fn create_data_at(&self, freq: usize) -> Vec<f32> {
+        self.create_save_data()
+            .create_data_at(freq, self.max_length)
     }
 
     pub fn render(
         &self,
         graphics: &mut Graphics<'_>,
-        theme: &Theme,
+        themes: &[Theme],
         active_theme: usize,
         saves: &[Option<SoundSave>],
+        export: &ExportOptions,
+        target: &NormaliseTarget,
     ) {
-        render_ui(self, graphics, theme, active_theme, &self.waveform, saves)
+        render_ui(self, graphics, themes, active_theme, saves, export, target)
     }
 }
 
+/// Semitones above C of the piano key, the bottom letter row are the white keys and the row above
+/// the black keys
+fn piano_key(key: KeyCode) -> Option<i32> {
+    let semitone = match key {
+        KeyCode::KeyZ => 0,
+        KeyCode::KeyS => 1,
+        KeyCode::KeyX => 2,
+        KeyCode::KeyD => 3,
+        KeyCode::KeyC => 4,
+        KeyCode::KeyV => 5,
+        KeyCode::KeyG => 6,
+        KeyCode::KeyB => 7,
+        KeyCode::KeyH => 8,
+        KeyCode::KeyN => 9,
+        KeyCode::KeyJ => 10,
+        KeyCode::KeyM => 11,
+        KeyCode::Comma => 12,
+        KeyCode::KeyL => 13,
+        KeyCode::Period => 14,
+        KeyCode::Semicolon => 15,
+        KeyCode::Slash => 16,
+        _ => return None,
+    };
+    Some(semitone)
+}
+
 pub enum State {
     Enabled(f32),
     Disabled(f32),
@@ -304,90 +602,116 @@ impl State {
     }
 }
 
-#[derive(Hash, Debug, Eq, PartialEq)]
+#[derive(Hash, Debug, Eq, PartialEq, Clone)]
 pub struct Item {
-    pub dec: char,
-    pub inc: char,
-    pub toggle: Option<char>,
-    pub dec_code: KeyCode,
-    pub inc_code: KeyCode,
-    pub toggle_code: Option<KeyCode>,
+    /// Can be turned on and off
+    pub toggle: bool,
     pub name: &'static str,
     pub item_type: ItemType,
+    pub page: Page,
 }
 
-#[derive(Hash, Debug, Eq, PartialEq)]
+#[derive(Hash, Debug, Eq, PartialEq, Copy, Clone)]
 pub enum ItemType {
     Float,
     Int,
+    /// Integer frequency in hz, steps by scale notes when snapping is on
+    Pitch,
+    /// Index into the list of names
+    Choice(&'static [&'static str]),
+}
+
+impl ItemType {
+    /// Change per key press, before shift or control
+    pub fn step(self) -> f32 {
+        match self {
+            ItemType::Float => 0.1,
+            ItemType::Int | ItemType::Pitch => 10.0,
+            ItemType::Choice(_) => 1.0,
+        }
+    }
+}
+
+#[derive(Hash, Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Page {
+    Main,
+    Modulation,
+    Sequence,
+    Tuning,
+}
+
+impl Page {
+    pub fn next(self) -> Page {
+        match self {
+            Page::Main => Page::Modulation,
+            Page::Modulation => Page::Sequence,
+            Page::Sequence => Page::Tuning,
+            Page::Tuning => Page::Main,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Page::Main => "MAIN",
+            Page::Modulation => "MODULATION",
+            Page::Sequence => "SEQUENCE",
+            Page::Tuning => "TUNING",
+        }
+    }
 }
 
 impl Item {
-    pub fn new(
-        dec: char,
-        inc: char,
-        dec_code: KeyCode,
-        inc_code: KeyCode,
-        name: &'static str,
-    ) -> Self {
+    pub fn new(name: &'static str) -> Self {
         Self {
-            dec,
-            inc,
-            dec_code,
-            inc_code,
             name,
             item_type: ItemType::Float,
-            toggle: None,
-            toggle_code: None,
+            toggle: false,
+            page: Page::Main,
         }
     }
 
-    pub fn new_tog(
-        tog: char,
-        dec: char,
-        inc: char,
-        tog_code: KeyCode,
-        dec_code: KeyCode,
-        inc_code: KeyCode,
-        name: &'static str,
-    ) -> Self {
+    pub fn new_tog(name: &'static str) -> Self {
         Self {
-            dec,
-            inc,
-            dec_code,
-            inc_code,
-            name,
-            item_type: ItemType::Float,
-            toggle: Some(tog),
-            toggle_code: Some(tog_code),
+            toggle: true,
+            ..Item::new(name)
         }
     }
 
-    pub fn new_int(
-        dec: char,
-        inc: char,
-        dec_code: KeyCode,
-        inc_code: KeyCode,
-        name: &'static str,
-    ) -> Self {
+    pub fn new_int(name: &'static str) -> Self {
         Self {
-            dec,
-            inc,
-            dec_code,
-            inc_code,
-            name,
             item_type: ItemType::Int,
-            toggle: None,
-            toggle_code: None,
+            ..Item::new(name)
         }
     }
+
+    pub fn on_page(mut self, page: Page) -> Self {
+        self.page = page;
+        self
+    }
+
+    pub fn with_type(mut self, item_type: ItemType) -> Self {
+        self.item_type = item_type;
+        self
+    }
 }
 
-const ITEM_VOLUME: &str = "Volume";
-const ITEM_ATTACK: &str = "Attack";
-const ITEM_DECAY: &str = "Decay";
-const ITEM_SUSTAIN: &str = "Sustain";
-const ITEM_RELEASE: &str = "Release";
-const ITEM_FREQ: &str = "Freq";
-const ITEM_CRUNCH: &str = "Crunch";
-const ITEM_DRIVE: &str = "Drive";
+pub const ITEM_VOLUME: &str = "Volume";
+pub const ITEM_ATTACK: &str = "Attack";
+pub const ITEM_DECAY: &str = "Decay";
+pub const ITEM_SUSTAIN: &str = "Sustain";
+pub const ITEM_RELEASE: &str = "Release";
+pub const ITEM_FREQ: &str = "Freq";
+pub const ITEM_CRUNCH: &str = "Crunch";
+pub const ITEM_DRIVE: &str = "Drive";
+pub const ITEM_VIBRATO: &str = "Vibrato";
+pub const ITEM_VIB_RATE: &str = "Vib Rate";
+pub const ITEM_VIB_DELAY: &str = "Vib Delay";
+pub const ITEM_TREMOLO: &str = "Tremolo";
+pub const ITEM_TREM_RATE: &str = "Trem Rate";
+pub const ITEM_TREM_DELAY: &str = "Trem Delay";
+pub const ITEM_ARPEGGIO: &str = "Arpeggio";
+pub const ITEM_ARP_TIME: &str = "Arp Time";
+pub const ITEM_REPEAT: &str = "Repeat";
+pub const ITEM_SNAP: &str = "Snap";
+pub const ITEM_ROOT: &str = "Root";
+pub const ITEM_TUNING: &str = "A4 Tuning";
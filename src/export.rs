@@ -0,0 +1,660 @@
+use crate::audio::SAMPLE_RATE;
+use crate::settings::SoundSave;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+pub const EXPORT_RATES: [u32; 3] = [SAMPLE_RATE, 22_050, 11_025];
+pub const DEFAULT_EXPORT_DIR: &str = "usfx_export";
+pub const DEFAULT_SPRITE_GAP: f32 = 0.1;
+const MANIFEST_NAME: &str = "manifest.json";
+const SPRITE_NAME: &str = "sprite";
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Default)]
+pub enum ExportFormat {
+    /// Signed 16 bit PCM WAV
+    #[default]
+    Wav16,
+    /// Unsigned 8 bit PCM WAV
+    Wav8,
+    /// Signed 16 bit big endian AIFF
+    Aiff,
+    /// Headerless signed 16 bit little endian
+    RawS16,
+    /// Headerless 32 bit float little endian
+    RawF32,
+    /// Rust source with a signed 16 bit array
+    RustI16,
+    /// Rust source with an unsigned 8 bit array
+    RustU8,
+    /// C header with a signed 16 bit array
+    CI16,
+    /// C header with an unsigned 8 bit array
+    CU8,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 9] = [
+        ExportFormat::Wav16,
+        ExportFormat::Wav8,
+        ExportFormat::Aiff,
+        ExportFormat::RawS16,
+        ExportFormat::RawF32,
+        ExportFormat::RustI16,
+        ExportFormat::RustU8,
+        ExportFormat::CI16,
+        ExportFormat::CU8,
+    ];
+    /// Formats used by every export, cycled with [Action::NextFormat](crate::keymap::Action)
+    pub const AUDIO: [ExportFormat; 5] = [
+        ExportFormat::Wav16,
+        ExportFormat::Wav8,
+        ExportFormat::Aiff,
+        ExportFormat::RawS16,
+        ExportFormat::RawF32,
+    ];
+    /// Formats only used when exporting the current sound, see [ExportOptions::source]
+    pub const SOURCE: [ExportFormat; 4] = [
+        ExportFormat::RustI16,
+        ExportFormat::RustU8,
+        ExportFormat::CI16,
+        ExportFormat::CU8,
+    ];
+
+    pub fn is_source(&self) -> bool {
+        ExportFormat::SOURCE.contains(self)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Wav16 => "wav16",
+            ExportFormat::Wav8 => "wav8",
+            ExportFormat::Aiff => "aiff",
+            ExportFormat::RawS16 => "s16le",
+            ExportFormat::RawF32 => "f32le",
+            ExportFormat::RustI16 => "rs16",
+            ExportFormat::RustU8 => "rs8",
+            ExportFormat::CI16 => "c16",
+            ExportFormat::CU8 => "c8",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Wav16 | ExportFormat::Wav8 => "wav",
+            ExportFormat::Aiff => "aiff",
+            ExportFormat::RawS16 | ExportFormat::RawF32 => "raw",
+            ExportFormat::RustI16 | ExportFormat::RustU8 => "rs",
+            ExportFormat::CI16 | ExportFormat::CU8 => "h",
+        }
+    }
+
+    /// Next audio format, source formats go to the first audio format
+    pub fn next(&self) -> ExportFormat {
+        let idx = ExportFormat::AUDIO.iter().position(|format| format == self);
+        ExportFormat::AUDIO[idx.map(|idx| idx + 1).unwrap_or(0) % ExportFormat::AUDIO.len()]
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        ExportFormat::ALL
+            .into_iter()
+            .find(|format| format.name() == value)
+            .with_context(|| format!("Unknown format {value}"))
+    }
+}
+
+/// File format and sample rate exported sounds are written with
+///
+/// `source` replaces `format` when exporting the current sound, if it's set
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    pub sample_rate: u32,
+    #[serde(default)]
+    pub source: Option<ExportFormat>,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        ExportOptions {
+            format: ExportFormat::default(),
+            sample_rate: SAMPLE_RATE,
+            source: None,
+        }
+    }
+}
+
+impl ExportOptions {
+    /// Switch to the next source format, after the last one no source format is used
+    pub fn next_source(&mut self) {
+        self.source = match self.source {
+            None => Some(ExportFormat::SOURCE[0]),
+            Some(source) => ExportFormat::SOURCE
+                .iter()
+                .position(|format| format == &source)
+                .and_then(|idx| ExportFormat::SOURCE.get(idx + 1))
+                .copied(),
+        };
+    }
+
+    /// Errors if the format isn't audio, source formats are only for single sounds
+    pub fn check_audio(&self) -> Result<()> {
+        if self.format.is_source() {
+            Err(anyhow!(
+                "{} can only be used for the current sound",
+                self.format.name()
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Switch to the next rate in [EXPORT_RATES]
+    pub fn next_rate(&mut self) {
+        let idx = EXPORT_RATES
+            .iter()
+            .position(|rate| rate == &self.sample_rate);
+        self.sample_rate = EXPORT_RATES[idx.map(|idx| idx + 1).unwrap_or(0) % EXPORT_RATES.len()];
+    }
+}
+
+/// Details of an exported sound, written to the manifest
+///
+/// `slot` is the index in [Settings::saved](crate::settings::Settings), `file` is the file name
+/// in the manifest's directory, `duration` is in seconds and `peak` is the largest absolute sample
+/// value
+#[derive(Debug, Clone, Serialize)]
+pub struct ManifestEntry {
+    pub slot: usize,
+    pub name: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+    pub file: String,
+    pub duration: f32,
+    pub peak: f32,
+}
+
+/// Render every saved sound to its own file in `dir` and write a manifest describing them
+///
+/// Sounds are limited to `max_length` seconds, returns the manifest entries, in slot order
+pub fn export_all(
+    saved: &[Option<SoundSave>],
+    options: &ExportOptions,
+    max_length: f32,
+    dir: &Path,
+) -> Result<Vec<ManifestEntry>> {
+    options.check_audio()?;
+    fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    let mut manifest = vec![];
+    for (slot, sound) in saved.iter().enumerate() {
+        if let Some(sound) = sound {
+            let data = sound.create_data(max_length);
+            let file = format!("{}.{}", file_stem(slot, sound), options.format.extension());
+            write_audio(&dir.join(&file), &data, options)?;
+            manifest.push(ManifestEntry {
+                slot,
+                name: sound.display_name(),
+                notes: sound.notes.clone(),
+                file,
+                duration: data.len() as f32 / SAMPLE_RATE as f32,
+                peak: peak(&data),
+            });
+        }
+    }
+    let json = serde_json::to_string_pretty(&manifest)?;
+    let path = dir.join(MANIFEST_NAME);
+    fs::write(&path, json).with_context(|| format!("writing {}", path.display()))?;
+    Ok(manifest)
+}
+
+/// Render `sound` (up to `max_length` seconds) to a file in `dir` named after it, in the source
+/// format if one is set
+///
+/// Returns the path of the file
+pub fn export_sound(
+    sound: &SoundSave,
+    options: &ExportOptions,
+    max_length: f32,
+    dir: &Path,
+) -> Result<PathBuf> {
+    fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    let options = ExportOptions {
+        format: options.source.unwrap_or(options.format),
+        ..*options
+    };
+    let path = dir.join(format!(
+        "{}.{}",
+        sanitise_name(&sound.display_name()),
+        options.format.extension()
+    ));
+    write_audio(&path, &sound.create_data(max_length), &options)?;
+    Ok(path)
+}
+
+/// Render the sounds in `slots` one after another, separated by `gap` seconds of silence, into
+/// a single file in `dir` along with a Howler.js sprite map
+///
+/// Empty slots are skipped and sounds are limited to `max_length` seconds, returns the path of the
+/// sprite map
+pub fn export_sprite(
+    saved: &[Option<SoundSave>],
+    slots: &[usize],
+    gap: f32,
+    options: &ExportOptions,
+    max_length: f32,
+    dir: &Path,
+) -> Result<PathBuf> {
+    options.check_audio()?;
+    fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    let gap = vec![0.0; (gap.max(0.0) * SAMPLE_RATE as f32) as usize];
+    let to_ms = |samples: usize| samples as f32 * 1000.0 / SAMPLE_RATE as f32;
+    let mut output = vec![];
+    let mut sprite = serde_json::Map::new();
+    for &slot in slots {
+        if let Some(Some(sound)) = saved.get(slot) {
+            if !output.is_empty() {
+                output.extend_from_slice(&gap);
+            }
+            let data = sound.create_data(max_length);
+            sprite.insert(
+                file_stem(slot, sound),
+                serde_json::json!([to_ms(output.len()), to_ms(data.len())]),
+            );
+            output.extend_from_slice(&data);
+        }
+    }
+    let audio_name = format!("{SPRITE_NAME}.{}", options.format.extension());
+    write_audio(&dir.join(&audio_name), &output, options)?;
+    let json = serde_json::json!({
+        "src": [audio_name],
+        "sprite": sprite,
+    });
+    let path = dir.join(format!("{SPRITE_NAME}.json"));
+    fs::write(&path, serde_json::to_string_pretty(&json)?)
+        .with_context(|| format!("writing {}", path.display()))?;
+    Ok(path)
+}
+
+/// Unique name for the sound in `slot`, used for files and sprite names
+pub fn file_stem(slot: usize, sound: &SoundSave) -> String {
+    format!("{slot:02}_{}", sanitise_name(&sound.display_name()))
+}
+
+/// Lowercase `name` and replace anything that isn't a letter or digit with a single underscore
+pub fn sanitise_name(name: &str) -> String {
+    let mut output = String::new();
+    for chr in name.chars() {
+        if chr.is_ascii_alphanumeric() {
+            output.push(chr.to_ascii_lowercase());
+        } else if !output.is_empty() && !output.ends_with('_') {
+            output.push('_');
+        }
+    }
+    let output = output.trim_end_matches('_');
+    if output.is_empty() {
+        String::from("sound")
+    } else {
+        output.to_string()
+    }
+}
+
+pub fn peak(data: &[f32]) -> f32 {
+    data.iter().fold(0.0, |peak, value| value.abs().max(peak))
+}
+
+/// Resample `data` from `from` hz to `to` hz
+///
+/// When downsampling each output sample is the average of the input samples it covers, to reduce
+/// aliasing, otherwise the input is linearly interpolated
+pub fn resample(data: &[f32], from: u32, to: u32) -> Vec<f32> {
+    if from == to || data.is_empty() {
+        return data.to_vec();
+    }
+    let ratio = from as f64 / to as f64;
+    let len = (data.len() as f64 / ratio).round().max(1.0) as usize;
+    (0..len)
+        .map(|i| {
+            let pos = i as f64 * ratio;
+            if ratio > 1.0 {
+                let start = (pos as usize).min(data.len() - 1);
+                let end = (((i + 1) as f64 * ratio) as usize).clamp(start + 1, data.len());
+                data[start..end].iter().sum::<f32>() / (end - start) as f32
+            } else {
+                let idx = (pos as usize).min(data.len() - 1);
+                let next = (idx + 1).min(data.len() - 1);
+                let frac = (pos - idx as f64) as f32;
+                data[idx] + (data[next] - data[idx]) * frac
+            }
+        })
+        .collect()
+}
+
+/// Resample `data` from [SAMPLE_RATE] and write it to `path` in the format from `options`
+///
+/// For source formats the array is named after the file
+pub fn write_audio(path: &Path, data: &[f32], options: &ExportOptions) -> Result<()> {
+    let data = resample(data, SAMPLE_RATE, options.sample_rate);
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    let bytes = encode(&data, options.format, options.sample_rate, &name);
+    fs::write(path, bytes).with_context(|| format!("writing {}", path.display()))
+}
+
+pub fn to_i16(value: f32) -> i16 {
+    (value * i16::MAX as f32) as i16
+}
+
+fn to_u8(value: f32) -> u8 {
+    (value * 127.0 + 128.0).round() as u8
+}
+
+/// Convert mono `data` to the file contents for `format`, clipping anything outside ±1.0
+///
+/// `name` is only used by source formats, for the array and constant names
+pub fn encode(data: &[f32], format: ExportFormat, sample_rate: u32, name: &str) -> Vec<u8> {
+    let data = data.iter().map(|value| value.clamp(-1.0, 1.0));
+    let ident = format!("SFX_{}", sanitise_name(name).to_uppercase());
+    match format {
+        ExportFormat::Wav16 => {
+            let samples: Vec<u8> = data.flat_map(|value| to_i16(value).to_le_bytes()).collect();
+            wav(&samples, 16, sample_rate)
+        }
+        ExportFormat::Wav8 => {
+            let samples: Vec<u8> = data.map(to_u8).collect();
+            wav(&samples, 8, sample_rate)
+        }
+        ExportFormat::Aiff => {
+            let samples: Vec<u8> = data.flat_map(|value| to_i16(value).to_be_bytes()).collect();
+            aiff(&samples, sample_rate)
+        }
+        ExportFormat::RawS16 => data.flat_map(|value| to_i16(value).to_le_bytes()).collect(),
+        ExportFormat::RawF32 => data.flat_map(|value| value.to_le_bytes()).collect(),
+        ExportFormat::RustI16 => {
+            let samples: Vec<String> = data.map(|value| to_i16(value).to_string()).collect();
+            rust_source(&samples, "i16", &ident, sample_rate)
+        }
+        ExportFormat::RustU8 => {
+            let samples: Vec<String> = data.map(|value| to_u8(value).to_string()).collect();
+            rust_source(&samples, "u8", &ident, sample_rate)
+        }
+        ExportFormat::CI16 => {
+            let samples: Vec<String> = data.map(|value| to_i16(value).to_string()).collect();
+            c_header(&samples, "int16_t", &ident, sample_rate)
+        }
+        ExportFormat::CU8 => {
+            let samples: Vec<String> = data.map(|value| to_u8(value).to_string()).collect();
+            c_header(&samples, "uint8_t", &ident, sample_rate)
+        }
+    }
+}
+
+/// Samples as comma separated lines for source formats
+fn source_lines(samples: &[String]) -> String {
+    samples
+        .chunks(16)
+        .map(|line| format!("    {},\n", line.join(", ")))
+        .collect()
+}
+
+fn rust_source(samples: &[String], ty: &str, ident: &str, sample_rate: u32) -> Vec<u8> {
+    let len = samples.len();
+    format!(
+        "pub const {ident}_RATE: u32 = {sample_rate};\n\
+        pub const {ident}_LEN: usize = {len};\n\
+        pub const {ident}: [{ty}; {ident}_LEN] = [\n{}];\n",
+        source_lines(samples)
+    )
+    .into_bytes()
+}
+
+fn c_header(samples: &[String], ty: &str, ident: &str, sample_rate: u32) -> Vec<u8> {
+    let len = samples.len();
+    format!(
+        "#pragma once\n\n\
+        #include <stdint.h>\n\n\
+        #define {ident}_RATE {sample_rate}\n\
+        #define {ident}_LEN {len}\n\n\
+        static const {ty} {ident}[{ident}_LEN] = {{\n{}}};\n",
+        source_lines(samples)
+    )
+    .into_bytes()
+}
+
+/// Mono PCM WAV file, `samples` must already be encoded
+fn wav(samples: &[u8], bits: u16, sample_rate: u32) -> Vec<u8> {
+    let block_align = bits / 8;
+    let data_len = samples.len() as u32;
+    let mut bytes = Vec::with_capacity(44 + samples.len());
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVE");
+    bytes.extend_from_slice(b"fmt ");
+    bytes.extend_from_slice(&16_u32.to_le_bytes());
+    //PCM
+    bytes.extend_from_slice(&1_u16.to_le_bytes());
+    //channels
+    bytes.extend_from_slice(&1_u16.to_le_bytes());
+    bytes.extend_from_slice(&sample_rate.to_le_bytes());
+    //byte rate
+    bytes.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+    bytes.extend_from_slice(&block_align.to_le_bytes());
+    bytes.extend_from_slice(&bits.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    bytes.extend_from_slice(samples);
+    bytes
+}
+
+/// Mono 16 bit AIFF file, `samples` must already be encoded as big endian
+fn aiff(samples: &[u8], sample_rate: u32) -> Vec<u8> {
+    let data_len = samples.len() as u32;
+    let mut bytes = Vec::with_capacity(54 + samples.len());
+    bytes.extend_from_slice(b"FORM");
+    bytes.extend_from_slice(&(46 + data_len).to_be_bytes());
+    bytes.extend_from_slice(b"AIFF");
+    bytes.extend_from_slice(b"COMM");
+    bytes.extend_from_slice(&18_u32.to_be_bytes());
+    //channels
+    bytes.extend_from_slice(&1_u16.to_be_bytes());
+    //frames
+    bytes.extend_from_slice(&(data_len / 2).to_be_bytes());
+    //bits per sample
+    bytes.extend_from_slice(&16_u16.to_be_bytes());
+    bytes.extend_from_slice(&extended(sample_rate));
+    bytes.extend_from_slice(b"SSND");
+    bytes.extend_from_slice(&(8 + data_len).to_be_bytes());
+    //offset and block size
+    bytes.extend_from_slice(&0_u32.to_be_bytes());
+    bytes.extend_from_slice(&0_u32.to_be_bytes());
+    bytes.extend_from_slice(samples);
+    bytes
+}
+
+/// `value` as an 80 bit IEEE extended float, as used by AIFF for the sample rate
+fn extended(value: u32) -> [u8; 10] {
+    let mut bytes = [0; 10];
+    if value == 0 {
+        return bytes;
+    }
+    let shift = value.leading_zeros();
+    let exponent = 16383 + 31 - shift as u16;
+    let mantissa = (value as u64) << (32 + shift);
+    bytes[..2].copy_from_slice(&exponent.to_be_bytes());
+    bytes[2..].copy_from_slice(&mantissa.to_be_bytes());
+    bytes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn u16_le(bytes: &[u8], at: usize) -> u16 {
+        u16::from_le_bytes([bytes[at], bytes[at + 1]])
+    }
+
+    fn u32_le(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    fn u32_be(bytes: &[u8], at: usize) -> u32 {
+        u32::from_be_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    /// Inverse of [extended], only handles whole numbers
+    fn from_extended(bytes: &[u8]) -> u32 {
+        let exponent = u16::from_be_bytes([bytes[0], bytes[1]]) as i32 - 16383;
+        let mantissa = u64::from_be_bytes(bytes[2..10].try_into().unwrap());
+        (mantissa >> (63 - exponent)) as u32
+    }
+
+    #[test]
+    fn wav16_header() {
+        let bytes = encode(&[0.0, 1.0, -1.0], ExportFormat::Wav16, 44_100, "");
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(u32_le(&bytes, 4) as usize, bytes.len() - 8);
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!(u16_le(&bytes, 20), 1);
+        assert_eq!(u16_le(&bytes, 22), 1);
+        assert_eq!(u32_le(&bytes, 24), 44_100);
+        assert_eq!(u32_le(&bytes, 28), 88_200);
+        assert_eq!(u16_le(&bytes, 32), 2);
+        assert_eq!(u16_le(&bytes, 34), 16);
+        assert_eq!(&bytes[36..40], b"data");
+        assert_eq!(u32_le(&bytes, 40), 6);
+        assert_eq!(u16_le(&bytes, 46) as i16, i16::MAX);
+        assert_eq!(u16_le(&bytes, 48) as i16, -i16::MAX);
+    }
+
+    #[test]
+    fn wav8_header() {
+        let bytes = encode(&[0.0, 1.0, -1.0, 2.0], ExportFormat::Wav8, 11_025, "");
+        assert_eq!(u32_le(&bytes, 4) as usize, bytes.len() - 8);
+        assert_eq!(u32_le(&bytes, 24), 11_025);
+        assert_eq!(u32_le(&bytes, 28), 11_025);
+        assert_eq!(u16_le(&bytes, 32), 1);
+        assert_eq!(u16_le(&bytes, 34), 8);
+        assert_eq!(u32_le(&bytes, 40), 4);
+        assert_eq!(&bytes[44..], &[128, 255, 1, 255]);
+    }
+
+    #[test]
+    fn aiff_header() {
+        for rate in [11_025, 22_050, 44_100, 48_000] {
+            let bytes = encode(&[0.0, 0.5, -0.5], ExportFormat::Aiff, rate, "");
+            assert_eq!(&bytes[0..4], b"FORM");
+            assert_eq!(u32_be(&bytes, 4) as usize, bytes.len() - 8);
+            assert_eq!(&bytes[8..16], b"AIFFCOMM");
+            assert_eq!(u32_be(&bytes, 16), 18);
+            assert_eq!(u16::from_be_bytes([bytes[20], bytes[21]]), 1);
+            assert_eq!(u32_be(&bytes, 22), 3);
+            assert_eq!(u16::from_be_bytes([bytes[26], bytes[27]]), 16);
+            assert_eq!(from_extended(&bytes[28..38]), rate);
+            assert_eq!(&bytes[38..42], b"SSND");
+            assert_eq!(u32_be(&bytes, 42), 8 + 6);
+            assert_eq!(bytes.len(), 54 + 6);
+        }
+    }
+
+    #[test]
+    fn raw_samples() {
+        let data = [0.25, -0.5];
+        let bytes = encode(&data, ExportFormat::RawF32, 44_100, "");
+        let decoded: Vec<f32> = bytes
+            .chunks(4)
+            .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+        assert_eq!(decoded, data);
+        let bytes = encode(&data, ExportFormat::RawS16, 44_100, "");
+        assert_eq!(bytes.len(), 4);
+        assert_eq!(u16_le(&bytes, 2) as i16, -i16::MAX / 2);
+    }
+
+    #[test]
+    fn resample_length() {
+        let data = vec![0.5; 44_100];
+        for rate in [44_100, 22_050, 11_025, 48_000] {
+            let output = resample(&data, 44_100, rate);
+            assert_eq!(output.len(), rate as usize);
+            assert!(output.iter().all(|value| (value - 0.5).abs() < 0.0001));
+        }
+    }
+
+    #[test]
+    fn source_arrays() {
+        let data = [0.0, 1.0, -1.0, 0.5, 2.0].repeat(4);
+        let i16s = ["0", "32767", "-32767", "16383", "32767"].repeat(4);
+        let u8s = ["128", "255", "1", "192", "255"].repeat(4);
+        for (format, ty, values) in [
+            (ExportFormat::RustI16, "i16", &i16s),
+            (ExportFormat::RustU8, "u8", &u8s),
+            (ExportFormat::CI16, "int16_t", &i16s),
+            (ExportFormat::CU8, "uint8_t", &u8s),
+        ] {
+            let source = String::from_utf8(encode(&data, format, 22_050, "Coin up!")).unwrap();
+            let (declarations, open, close) = match format {
+                ExportFormat::RustI16 | ExportFormat::RustU8 => (
+                    [
+                        String::from("pub const SFX_COIN_UP_RATE: u32 = 22050;"),
+                        String::from("pub const SFX_COIN_UP_LEN: usize = 20;"),
+                        format!("pub const SFX_COIN_UP: [{ty}; SFX_COIN_UP_LEN] = ["),
+                    ],
+                    "= [",
+                    "];",
+                ),
+                _ => (
+                    [
+                        String::from("#define SFX_COIN_UP_RATE 22050"),
+                        String::from("#define SFX_COIN_UP_LEN 20"),
+                        format!("static const {ty} SFX_COIN_UP[SFX_COIN_UP_LEN] = {{"),
+                    ],
+                    "= {",
+                    "};",
+                ),
+            };
+            for declaration in declarations {
+                assert!(source.contains(&declaration), "{declaration} in {source}");
+            }
+            let array = source
+                .split_once(open)
+                .unwrap()
+                .1
+                .split_once(close)
+                .unwrap()
+                .0;
+            let array: Vec<&str> = array
+                .split(',')
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .collect();
+            assert_eq!(&array, values);
+        }
+    }
+
+    #[test]
+    fn source_formats_only_for_current() {
+        let mut options = ExportOptions::default();
+        for _ in 0..ExportFormat::AUDIO.len() {
+            options.format = options.format.next();
+            assert!(options.check_audio().is_ok());
+        }
+        assert_eq!(options.format, ExportFormat::default());
+        let mut sources = vec![];
+        options.next_source();
+        while let Some(source) = options.source {
+            sources.push(source);
+            options.next_source();
+        }
+        assert_eq!(sources, ExportFormat::SOURCE);
+        options.format = ExportFormat::CU8;
+        assert!(options.check_audio().is_err());
+        assert_eq!(options.format.next(), ExportFormat::Wav16);
+    }
+}
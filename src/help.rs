@@ -0,0 +1,219 @@
+use crate::controller::{Item, ItemType};
+use crate::keymap::{Action, KeyMap};
+use crate::theme::Theme;
+use crate::{SN, SR};
+use pixels_graphics_lib::buffer_graphics_lib::prelude::TextPos::Px;
+use pixels_graphics_lib::buffer_graphics_lib::prelude::*;
+use pixels_graphics_lib::prelude::PixelFont::{Standard4x5, Standard6x7};
+use pixels_graphics_lib::prelude::SceneUpdateResult::{Nothing, Pop};
+use pixels_graphics_lib::prelude::*;
+
+const TOP: isize = 24;
+const ROW_HEIGHT: isize = 7;
+const LEFT_COLUMN: isize = 12;
+const RIGHT_COLUMN: isize = 176;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HelpSetup {
+    pub keys: KeyMap,
+    pub theme: Theme,
+    pub items: Vec<Item>,
+}
+
+/// Lists every binding over the main scene, closed with Escape or the help key
+pub struct HelpScene {
+    keys: KeyMap,
+    theme: Theme,
+    items: Vec<Item>,
+    result: SceneUpdateResult<SR, SN>,
+}
+
+impl HelpScene {
+    pub fn new(setup: HelpSetup) -> Self {
+        HelpScene {
+            keys: setup.keys,
+            theme: setup.theme,
+            items: setup.items,
+            result: Nothing,
+        }
+    }
+}
+
+/// How much one press changes the item by
+fn step_text(item_type: ItemType) -> String {
+    match item_type {
+        ItemType::Choice(_) => String::from("NEXT"),
+        ItemType::Pitch => format!("{} HZ", item_type.step()),
+        _ => item_type.step().to_string(),
+    }
+}
+
+impl HelpScene {
+    fn draw_items(&self, graphics: &mut Graphics) {
+        let theme = &self.theme;
+        let mut y = TOP;
+        let mut page = None;
+        for item in &self.items {
+            if page != Some(item.page) {
+                if page.is_some() {
+                    y += ROW_HEIGHT / 2;
+                }
+                page = Some(item.page);
+                graphics.draw_text(
+                    &format!("{} PAGE", item.page.name()),
+                    Px(LEFT_COLUMN, y),
+                    (theme.active, Standard4x5),
+                );
+                y += ROW_HEIGHT;
+            }
+            let mut keys = format!(
+                "{} {}",
+                self.keys.label(Action::Decrease(item.name)),
+                self.keys.label(Action::Increase(item.name))
+            );
+            if item.toggle {
+                keys = format!("{} {keys}", self.keys.label(Action::Toggle(item.name)));
+            }
+            graphics.draw_text(
+                &item.name.to_uppercase(),
+                Px(LEFT_COLUMN, y),
+                (theme.inactive, Standard4x5),
+            );
+            graphics.draw_text(
+                &keys,
+                Px(LEFT_COLUMN + 58, y),
+                (theme.inactive, Standard4x5),
+            );
+            graphics.draw_text(
+                &step_text(item.item_type),
+                Px(LEFT_COLUMN + 112, y),
+                (theme.inactive, Standard4x5),
+            );
+            y += ROW_HEIGHT;
+        }
+        y += ROW_HEIGHT / 2;
+        for line in [
+            "FIRST KEY TOGGLES, THEN DEC/INC",
+            "HOLD TO REPEAT",
+            "+SHIFT STEP X10",
+            "+CTRL STEP /10",
+            "SNAPPED FREQ STEPS BY NOTE",
+            "+SHIFT BY OCTAVE",
+        ] {
+            graphics.draw_text(line, Px(LEFT_COLUMN, y), (theme.inactive, Standard4x5));
+            y += ROW_HEIGHT;
+        }
+    }
+
+    fn draw_actions(&self, graphics: &mut Graphics) {
+        let theme = &self.theme;
+        let mut y = TOP;
+        for (action, _) in self.keys.bindings() {
+            let (name, key, notes) = match action {
+                _ if action.item().is_some() => continue,
+                Action::SaveSlot(0) => (
+                    String::from("SAVE SLOTS"),
+                    format!(
+                        "{}-{}",
+                        self.keys.label(Action::SaveSlot(0)),
+                        self.keys.label(Action::SaveSlot(9))
+                    ),
+                    vec!["+SHIFT LOAD", "+CTRL DELETE", "+ALT RENAME"],
+                ),
+                Action::SaveSlot(_) => continue,
+                Action::NextFormat => (
+                    action.name().to_uppercase(),
+                    self.keys.label(action).to_string(),
+                    vec!["+SHIFT SOURCE FOR CURRENT"],
+                ),
+                Action::Normalise => (
+                    action.name().to_uppercase(),
+                    self.keys.label(action).to_string(),
+                    vec!["+SHIFT ALL SAVED", "+CTRL PEAK/LOUDNESS"],
+                ),
+                _ => (
+                    action.name().to_uppercase(),
+                    self.keys.label(action).to_string(),
+                    vec![],
+                ),
+            };
+            graphics.draw_text(&name, Px(RIGHT_COLUMN, y), (theme.inactive, Standard4x5));
+            graphics.draw_text(
+                &key,
+                Px(330, y),
+                (theme.inactive, Standard4x5, Positioning::RightTop),
+            );
+            y += ROW_HEIGHT;
+            for note in notes {
+                graphics.draw_text(note, Px(RIGHT_COLUMN + 8, y), (theme.inactive, Standard4x5));
+                y += ROW_HEIGHT;
+            }
+        }
+        self.draw_keyboard_mode(graphics, y + ROW_HEIGHT / 2);
+    }
+
+    /// Piano keys aren't bindable so they're listed separately
+    fn draw_keyboard_mode(&self, graphics: &mut Graphics, mut y: isize) {
+        let theme = &self.theme;
+        graphics.draw_text(
+            "KEYBOARD MODE",
+            Px(RIGHT_COLUMN, y),
+            (theme.active, Standard4x5),
+        );
+        y += ROW_HEIGHT;
+        for (name, keys) in [
+            ("WHITE KEYS", "Z X C V B N M , . /"),
+            ("BLACK KEYS", "S D G H J L ;"),
+            ("OCTAVE", "DOWN UP"),
+        ] {
+            graphics.draw_text(name, Px(RIGHT_COLUMN, y), (theme.inactive, Standard4x5));
+            graphics.draw_text(
+                keys,
+                Px(330, y),
+                (theme.inactive, Standard4x5, Positioning::RightTop),
+            );
+            y += ROW_HEIGHT;
+        }
+        for note in ["PLAYS C TO THE NEXT E", "+SHIFT FOR THE OCTAVE ABOVE"] {
+            graphics.draw_text(note, Px(RIGHT_COLUMN + 8, y), (theme.inactive, Standard4x5));
+            y += ROW_HEIGHT;
+        }
+    }
+}
+
+impl Scene<SR, SN> for HelpScene {
+    fn render(&self, graphics: &mut Graphics, _: &MouseData, _: &FxHashSet<KeyCode>) {
+        let theme = &self.theme;
+        let rect = Rect::new((4, 4), (336, 366));
+        graphics.draw_rect(rect.clone(), fill(theme.background));
+        graphics.draw_rect(rect, stroke(theme.active));
+        graphics.draw_text("HELP", Px(LEFT_COLUMN, 10), (theme.active, Standard6x7));
+        graphics.draw_text(
+            &format!("[ESC] OR [{}] CLOSE", self.keys.label(Action::Help)),
+            Px(330, 11),
+            (theme.inactive, Standard4x5, Positioning::RightTop),
+        );
+        self.draw_items(graphics);
+        self.draw_actions(graphics);
+    }
+
+    fn on_key_down(&mut self, key: KeyCode, _: &MouseData, _: &FxHashSet<KeyCode>) {
+        if key == KeyCode::Escape || self.keys.is(key, Action::Help) {
+            self.result = Pop(None);
+        }
+    }
+
+    fn update(
+        &mut self,
+        _: &Timing,
+        _: &MouseData,
+        _: &FxHashSet<KeyCode>,
+        _: &Window,
+    ) -> SceneUpdateResult<SR, SN> {
+        self.result.clone()
+    }
+
+    fn is_dialog(&self) -> bool {
+        true
+    }
+}
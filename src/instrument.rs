@@ -0,0 +1,325 @@
+use crate::audio::SAMPLE_RATE;
+use crate::export::{encode, resample, to_i16, ExportFormat};
+use crate::notes::Tuning;
+use crate::settings::SoundSave;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Length of the sustain loop, the actual loop is rounded to a whole number of cycles
+const LOOP_SECS: f32 = 0.05;
+/// Zero samples required after each sample in an SF2 file
+const SF2_PADDING: usize = 46;
+
+/// Sound rendered at a MIDI note, ready to be written as an instrument
+///
+/// `tune` is in cents and corrects for the frequency being rounded to a whole number when
+/// rendering, `loop_start` and `loop_end` are in samples and cover the sustain region
+struct Instrument {
+    name: String,
+    data: Vec<f32>,
+    sample_rate: u32,
+    key: i32,
+    tune: f32,
+    loop_start: usize,
+    loop_end: usize,
+    release: f32,
+}
+
+impl Instrument {
+    /// Render `sound` at the standard tuning note nearest to its frequency
+    fn new(sound: &SoundSave, name: &str, sample_rate: u32, max_length: f32) -> Self {
+        let tuning = Tuning::default();
+        let key = tuning.midi_note(sound.freq() as f32);
+        let exact = tuning.note_freq(key);
+        let freq = exact.round().max(1.0);
+        let data = resample(
+            &sound.create_data_at(freq as usize, max_length),
+            SAMPLE_RATE,
+            sample_rate,
+        );
+        let period = sample_rate as f32 / freq;
+        let cycles = (LOOP_SECS * freq).round().max(1.0);
+        //SF2 requires at least 8 samples after the loop
+        let last = data.len().saturating_sub(8);
+        let loop_start = (((sound.attack + sound.decay) * sample_rate as f32) as usize).min(last);
+        let loop_end = (loop_start + (cycles * period).round() as usize).min(last);
+        Instrument {
+            name: name.to_string(),
+            data,
+            sample_rate,
+            key,
+            tune: 1200.0 * (exact / freq).log2(),
+            loop_start,
+            loop_end,
+            release: sound.release,
+        }
+    }
+
+    fn sfz(&self, wav_name: &str) -> String {
+        format!(
+            "<region>\n\
+            sample={wav_name}\n\
+            lokey=0\n\
+            hikey=127\n\
+            pitch_keycenter={}\n\
+            tune={}\n\
+            loop_mode=loop_sustain\n\
+            loop_start={}\n\
+            loop_end={}\n\
+            ampeg_release={:.3}\n",
+            self.key,
+            self.tune.round() as i32,
+            self.loop_start,
+            self.loop_end.saturating_sub(1),
+            self.release
+        )
+    }
+
+    fn sf2(&self) -> Vec<u8> {
+        let mut info = b"INFO".to_vec();
+        //version 2.01
+        info.extend(chunk(b"ifil", &[2, 0, 1, 0]));
+        info.extend(chunk(b"isng", &name_bytes("EMU8000", 8)));
+        info.extend(chunk(b"INAM", &name_bytes(&self.name, 20)));
+
+        let mut samples: Vec<u8> = self
+            .data
+            .iter()
+            .flat_map(|value| to_i16(value.clamp(-1.0, 1.0)).to_le_bytes())
+            .collect();
+        samples.resize(samples.len() + SF2_PADDING * 2, 0);
+        let mut sdta = b"sdta".to_vec();
+        sdta.extend(chunk(b"smpl", &samples));
+
+        let name = name_bytes(&self.name, 20);
+        let mut phdr = name.clone();
+        //preset, bank, bag index, library, genre, morphology
+        phdr.extend(record(&[0, 0, 0], &[0, 0, 0]));
+        phdr.extend(name_bytes("EOP", 20));
+        phdr.extend(record(&[0, 0, 1], &[0, 0, 0]));
+        //generator index, modulator index
+        let pbag = record(&[0, 0, 1, 0], &[]);
+        let pmod = vec![0; 10];
+        //instrument 0, terminal
+        let pgen = record(&[41, 0, 0, 0], &[]);
+        let mut inst = name.clone();
+        inst.extend(0_u16.to_le_bytes());
+        inst.extend(name_bytes("EOI", 20));
+        inst.extend(1_u16.to_le_bytes());
+        let ibag = record(&[0, 0, 5, 0], &[]);
+        let imod = vec![0; 10];
+        let release = (1200.0 * self.release.max(0.001).log2()).round() as i16;
+        let igen = record(
+            &[
+                //key range 0-127
+                43,
+                0x7F00,
+                //release time in timecents
+                38,
+                release as u16,
+                //loop while the key is held then play the rest
+                54,
+                3,
+                //root key
+                58,
+                self.key.clamp(0, 127) as u16,
+                //sample 0, must be last
+                53,
+                0,
+                //terminal
+                0,
+                0,
+            ],
+            &[],
+        );
+        let mut shdr = name;
+        //start, end, loop start, loop end, sample rate
+        shdr.extend(record(
+            &[],
+            &[
+                0,
+                self.data.len() as u32,
+                self.loop_start as u32,
+                self.loop_end as u32,
+                self.sample_rate,
+            ],
+        ));
+        shdr.push(self.key.clamp(0, 127) as u8);
+        shdr.push(self.tune.round() as i8 as u8);
+        //sample link, mono sample type
+        shdr.extend(record(&[0, 1], &[]));
+        shdr.extend(name_bytes("EOS", 20));
+        shdr.extend(vec![0; 26]);
+        let mut pdta = b"pdta".to_vec();
+        for (id, data) in [
+            (b"phdr", phdr),
+            (b"pbag", pbag),
+            (b"pmod", pmod),
+            (b"pgen", pgen),
+            (b"inst", inst),
+            (b"ibag", ibag),
+            (b"imod", imod),
+            (b"igen", igen),
+            (b"shdr", shdr),
+        ] {
+            pdta.extend(chunk(id, &data));
+        }
+
+        let mut sfbk = b"sfbk".to_vec();
+        sfbk.extend(chunk(b"LIST", &info));
+        sfbk.extend(chunk(b"LIST", &sdta));
+        sfbk.extend(chunk(b"LIST", &pdta));
+        chunk(b"RIFF", &sfbk)
+    }
+}
+
+/// Render `sound` as an instrument and write it to `dir` as both an SFZ (with a 16 bit WAV) and an
+/// SF2 file, named `name`, returns the path of the SFZ file
+///
+/// The sound is limited to `max_length` seconds
+pub fn export_instrument(
+    sound: &SoundSave,
+    name: &str,
+    sample_rate: u32,
+    max_length: f32,
+    dir: &Path,
+) -> Result<PathBuf> {
+    fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    let instrument = Instrument::new(sound, name, sample_rate, max_length);
+    let wav_name = format!("{}.wav", instrument.name);
+    let wav = encode(&instrument.data, ExportFormat::Wav16, sample_rate, "");
+    for (path, bytes) in [
+        (dir.join(&wav_name), wav),
+        (
+            dir.join(format!("{}.sfz", instrument.name)),
+            instrument.sfz(&wav_name).into_bytes(),
+        ),
+        (
+            dir.join(format!("{}.sf2", instrument.name)),
+            instrument.sf2(),
+        ),
+    ] {
+        fs::write(&path, bytes).with_context(|| format!("writing {}", path.display()))?;
+    }
+    Ok(dir.join(format!("{}.sfz", instrument.name)))
+}
+
+/// RIFF chunk, padded to an even length
+fn chunk(id: &[u8; 4], data: &[u8]) -> Vec<u8> {
+    let mut bytes = id.to_vec();
+    bytes.extend((data.len() as u32).to_le_bytes());
+    bytes.extend(data);
+    if data.len() % 2 == 1 {
+        bytes.push(0);
+    }
+    bytes
+}
+
+/// `name` as ASCII, truncated and zero padded to `len` bytes, always ending in at least one zero
+fn name_bytes(name: &str, len: usize) -> Vec<u8> {
+    let mut bytes: Vec<u8> = name.bytes().filter(u8::is_ascii).take(len - 1).collect();
+    bytes.resize(len, 0);
+    bytes
+}
+
+/// Little endian words followed by double words
+fn record(words: &[u16], dwords: &[u32]) -> Vec<u8> {
+    words
+        .iter()
+        .flat_map(|word| word.to_le_bytes())
+        .chain(dwords.iter().flat_map(|dword| dword.to_le_bytes()))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::render::DEFAULT_MAX_LENGTH;
+
+    fn u32_le(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    /// Split a list of RIFF chunks into their ids and data, checking each size fits
+    fn chunks(mut bytes: &[u8]) -> Vec<(&[u8], &[u8])> {
+        let mut output = vec![];
+        while !bytes.is_empty() {
+            let len = u32_le(bytes, 4) as usize;
+            assert!(8 + len <= bytes.len());
+            output.push((&bytes[..4], &bytes[8..8 + len]));
+            bytes = &bytes[(8 + len + len % 2).min(bytes.len())..];
+        }
+        output
+    }
+
+    fn instrument() -> Instrument {
+        let mut sound = SoundSave::new_blank();
+        sound.volume = 1.0;
+        sound.attack = 0.1;
+        sound.decay = 0.2;
+        sound.sustain = 0.5;
+        sound.release = 2.0;
+        sound.freq = 440;
+        Instrument::new(&sound, "test", 22_050, DEFAULT_MAX_LENGTH)
+    }
+
+    #[test]
+    fn sf2_chunk_sizes() {
+        let instrument = instrument();
+        let bytes = instrument.sf2();
+        let riff = chunks(&bytes);
+        assert_eq!(riff.len(), 1);
+        assert_eq!(riff[0].0, b"RIFF");
+        assert_eq!(u32_le(&bytes, 4) as usize, bytes.len() - 8);
+        let sfbk = riff[0].1;
+        assert_eq!(&sfbk[..4], b"sfbk");
+        let lists = chunks(&sfbk[4..]);
+        let kinds: Vec<&[u8]> = lists.iter().map(|(_, data)| &data[..4]).collect();
+        assert_eq!(kinds, [b"INFO", b"sdta", b"pdta"]);
+        assert!(lists.iter().all(|(id, _)| id == b"LIST"));
+        let smpl = chunks(&lists[1].1[4..]);
+        assert_eq!(smpl[0].0, b"smpl");
+        assert_eq!(smpl[0].1.len(), (instrument.data.len() + SF2_PADDING) * 2);
+        let sizes: Vec<(&[u8], usize)> = chunks(&lists[2].1[4..])
+            .into_iter()
+            .map(|(id, data)| (id, data.len()))
+            .collect();
+        let expected: [(&[u8], usize); 9] = [
+            (b"phdr", 76),
+            (b"pbag", 8),
+            (b"pmod", 10),
+            (b"pgen", 8),
+            (b"inst", 44),
+            (b"ibag", 8),
+            (b"imod", 10),
+            (b"igen", 24),
+            (b"shdr", 92),
+        ];
+        assert_eq!(sizes, expected);
+    }
+
+    #[test]
+    fn shdr_loop_points() {
+        let instrument = instrument();
+        let bytes = instrument.sf2();
+        let shdr = bytes
+            .windows(4)
+            .position(|id| id == b"shdr")
+            .map(|at| &bytes[at + 8..])
+            .unwrap();
+        let loop_start = ((0.1 + 0.2) * 22_050.0) as u32;
+        assert_eq!(u32_le(shdr, 20), 0);
+        assert_eq!(u32_le(shdr, 24) as usize, instrument.data.len());
+        assert_eq!(u32_le(shdr, 28), loop_start);
+        assert_eq!(u32_le(shdr, 36), 22_050);
+        //a whole number of cycles of 440hz, around LOOP_SECS long
+        let loop_len = u32_le(shdr, 32) - loop_start;
+        let cycles = loop_len as f32 * 440.0 / 22_050.0;
+        assert!((cycles - (LOOP_SECS * 440.0).round()).abs() < 0.05);
+        assert_eq!(shdr[40], 69);
+        assert!(instrument
+            .sfz("test.wav")
+            .contains(&format!("loop_start={loop_start}\n")));
+    }
+}
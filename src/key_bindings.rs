@@ -0,0 +1,179 @@
+use crate::controller::Page;
+use crate::keymap::{is_bindable, Action, KeyMap};
+use crate::theme::Theme;
+use crate::{SN, SR};
+use pixels_graphics_lib::buffer_graphics_lib::prelude::TextPos::Px;
+use pixels_graphics_lib::buffer_graphics_lib::prelude::*;
+use pixels_graphics_lib::prelude::PixelFont::{Standard4x5, Standard6x7};
+use pixels_graphics_lib::prelude::SceneUpdateResult::{Nothing, Pop};
+use pixels_graphics_lib::prelude::*;
+
+const LIST_Y: isize = 22;
+const ROW_HEIGHT: isize = 9;
+const VISIBLE_ROWS: usize = 34;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyBindingsSetup {
+    pub keys: KeyMap,
+    pub theme: Theme,
+    /// Page of each item, item actions only clash with actions on the same page
+    pub pages: Vec<(&'static str, Page)>,
+}
+
+/// List of every action and its key, on closing the bindings are returned to the main scene
+pub struct KeyBindingsScene {
+    keys: KeyMap,
+    theme: Theme,
+    pages: Vec<(&'static str, Page)>,
+    actions: Vec<Action>,
+    selected: usize,
+    /// First visible row
+    scroll: usize,
+    /// Waiting for a key to bind to the selected action
+    listening: bool,
+    result: SceneUpdateResult<SR, SN>,
+}
+
+impl KeyBindingsScene {
+    pub fn new(setup: KeyBindingsSetup) -> Self {
+        KeyBindingsScene {
+            actions: setup.keys.bindings().map(|(action, _)| action).collect(),
+            keys: setup.keys,
+            theme: setup.theme,
+            pages: setup.pages,
+            selected: 0,
+            scroll: 0,
+            listening: false,
+            result: Nothing,
+        }
+    }
+}
+
+impl KeyBindingsScene {
+    fn page(&self, action: Action) -> Option<Page> {
+        let item = action.item()?;
+        self.pages
+            .iter()
+            .find(|(name, _)| *name == item)
+            .map(|(_, page)| *page)
+    }
+
+    /// True if another action that can be used at the same time has the same key
+    fn clashes(&self, action: Action) -> bool {
+        let key = self.keys.key(action);
+        let page = self.page(action);
+        self.keys.bindings().any(|(other, other_key)| {
+            other != action
+                && Some(other_key) == key
+                && match (page, self.page(other)) {
+                    (Some(page), Some(other_page)) => page == other_page,
+                    _ => true,
+                }
+        })
+    }
+
+    fn select(&mut self, idx: usize) {
+        self.selected = idx.min(self.actions.len() - 1);
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.selected >= self.scroll + VISIBLE_ROWS {
+            self.scroll = self.selected + 1 - VISIBLE_ROWS;
+        }
+    }
+}
+
+impl Scene<SR, SN> for KeyBindingsScene {
+    fn render(&self, graphics: &mut Graphics, _: &MouseData, _: &FxHashSet<KeyCode>) {
+        let theme = &self.theme;
+        graphics.clear(theme.background);
+        graphics.draw_text("KEY BINDINGS", Px(4, 4), (theme.active, Standard6x7));
+
+        for (row, action) in self
+            .actions
+            .iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(VISIBLE_ROWS)
+        {
+            let y = LIST_Y + (row - self.scroll) as isize * ROW_HEIGHT;
+            let selected = row == self.selected;
+            let color = if self.clashes(*action) {
+                theme.warning
+            } else if selected {
+                theme.active
+            } else {
+                theme.inactive
+            };
+            if selected {
+                graphics.draw_text(">", Px(4, y), (theme.active, Standard4x5));
+            }
+            graphics.draw_text(
+                &action.name().to_uppercase(),
+                Px(12, y),
+                (color, Standard4x5),
+            );
+            if let Some(page) = self.page(*action) {
+                graphics.draw_text(page.name(), Px(180, y), (theme.disabled, Standard4x5));
+            }
+            let key = if selected && self.listening {
+                String::from("PRESS A KEY")
+            } else {
+                self.keys.key_name(*action).to_uppercase()
+            };
+            graphics.draw_text(&key, Px(250, y), (color, Standard4x5));
+        }
+
+        let help = if self.listening {
+            vec!["[ESC] CANCEL"]
+        } else {
+            vec![
+                "[UP/DOWN] SELECT  [ENTER] CHANGE  [DEL] RESET",
+                "[ESC] DONE",
+            ]
+        };
+        for (i, line) in help.iter().enumerate() {
+            graphics.draw_text(
+                line,
+                Px(4, 336 + i as isize * 9),
+                (theme.inactive, Standard4x5),
+            );
+        }
+        graphics.draw_text(
+            "RED KEYS ARE USED BY MORE THAN ONE ACTION",
+            Px(4, 358),
+            (theme.warning, Standard4x5),
+        );
+    }
+
+    fn on_key_down(&mut self, key: KeyCode, _: &MouseData, _: &FxHashSet<KeyCode>) {
+        if self.listening {
+            if key == KeyCode::Escape {
+                self.listening = false;
+            } else if is_bindable(key) {
+                self.keys.bind(self.actions[self.selected], key);
+                self.listening = false;
+            }
+            return;
+        }
+        match key {
+            KeyCode::ArrowUp => self.select(self.selected.saturating_sub(1)),
+            KeyCode::ArrowDown => self.select(self.selected + 1),
+            KeyCode::PageUp => self.select(self.selected.saturating_sub(VISIBLE_ROWS)),
+            KeyCode::PageDown => self.select(self.selected + VISIBLE_ROWS),
+            KeyCode::Enter | KeyCode::NumpadEnter => self.listening = true,
+            KeyCode::Delete | KeyCode::Backspace => self.keys.reset(self.actions[self.selected]),
+            KeyCode::Escape => self.result = Pop(Some(SR::Keys(self.keys.clone()))),
+            _ => {}
+        }
+    }
+
+    fn update(
+        &mut self,
+        _: &Timing,
+        _: &MouseData,
+        _: &FxHashSet<KeyCode>,
+        _: &Window,
+    ) -> SceneUpdateResult<SR, SN> {
+        self.result.clone()
+    }
+}
@@ -0,0 +1,434 @@
+use crate::controller::*;
+use indexmap::IndexMap;
+use pixels_graphics_lib::prelude::KeyCode;
+use std::collections::BTreeMap;
+use usfx::{DutyCycle, OscillatorType};
+
+/// Everything that can be bound to a key
+///
+/// Shift and control aren't bindable, they still change the size of steps and turn saving into
+/// loading or deleting
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Action {
+    /// Item name
+    Decrease(&'static str),
+    Increase(&'static str),
+    Toggle(&'static str),
+    Oscillator(OscillatorType),
+    Duty(DutyCycle),
+    Play,
+    NextPage,
+    KeyboardMode,
+    WaveformMode,
+    PrevRegister,
+    NextRegister,
+    PrevTheme,
+    NextTheme,
+    ThemeEditor,
+    KeyBindings,
+    Help,
+    ExportAll,
+    ExportSprite,
+    NextFormat,
+    NextRate,
+    ExportCurrent,
+    ExportInstrument,
+    ExportVariations,
+    Normalise,
+    RestoreDeleted,
+    EditName,
+    /// Slot index, 0 is shown as 1
+    SaveSlot(usize),
+    Quit,
+}
+
+impl Action {
+    /// Used in settings and shown when rebinding
+    pub fn name(&self) -> String {
+        match self {
+            Action::Decrease(item) => format!("Decrease {item}"),
+            Action::Increase(item) => format!("Increase {item}"),
+            Action::Toggle(item) => format!("Toggle {item}"),
+            Action::Oscillator(osc) => format!("{osc:?} oscillator"),
+            Action::Duty(cycle) => format!("{cycle:?} duty cycle"),
+            Action::Play => String::from("Play"),
+            Action::NextPage => String::from("Next page"),
+            Action::KeyboardMode => String::from("Keyboard mode"),
+            Action::WaveformMode => String::from("Waveform mode"),
+            Action::PrevRegister => String::from("Previous register"),
+            Action::NextRegister => String::from("Next register"),
+            Action::PrevTheme => String::from("Previous theme"),
+            Action::NextTheme => String::from("Next theme"),
+            Action::ThemeEditor => String::from("Theme editor"),
+            Action::KeyBindings => String::from("Key bindings"),
+            Action::Help => String::from("Help"),
+            Action::ExportAll => String::from("Export all"),
+            Action::ExportSprite => String::from("Export sprite"),
+            Action::NextFormat => String::from("Export format"),
+            Action::NextRate => String::from("Export rate"),
+            Action::ExportCurrent => String::from("Export current"),
+            Action::ExportInstrument => String::from("Export instrument"),
+            Action::ExportVariations => String::from("Export variations"),
+            Action::Normalise => String::from("Normalise"),
+            Action::RestoreDeleted => String::from("Restore deleted"),
+            Action::EditName => String::from("Edit name"),
+            Action::SaveSlot(slot) => format!("Save slot {}", (slot + 1) % 10),
+            Action::Quit => String::from("Quit"),
+        }
+    }
+
+    /// Item the action changes, these only apply on the item's page
+    pub fn item(&self) -> Option<&'static str> {
+        match self {
+            Action::Decrease(item) | Action::Increase(item) | Action::Toggle(item) => Some(item),
+            _ => None,
+        }
+    }
+
+    /// Repeats while the key is held
+    pub fn repeats(&self) -> bool {
+        matches!(self, Action::Decrease(_) | Action::Increase(_))
+    }
+
+    /// Overwrites or deletes saved sounds, these are locked out briefly so a double press can't do it twice
+    pub fn is_destructive(&self) -> bool {
+        matches!(self, Action::SaveSlot(_) | Action::Normalise)
+    }
+}
+
+fn default_bindings() -> Vec<(Action, KeyCode)> {
+    use Action::*;
+    let mut bindings = vec![
+        (Decrease(ITEM_VOLUME), KeyCode::KeyQ),
+        (Increase(ITEM_VOLUME), KeyCode::KeyW),
+        (Decrease(ITEM_ATTACK), KeyCode::KeyA),
+        (Increase(ITEM_ATTACK), KeyCode::KeyS),
+        (Decrease(ITEM_DECAY), KeyCode::KeyZ),
+        (Increase(ITEM_DECAY), KeyCode::KeyX),
+        (Decrease(ITEM_SUSTAIN), KeyCode::KeyE),
+        (Increase(ITEM_SUSTAIN), KeyCode::KeyR),
+        (Decrease(ITEM_RELEASE), KeyCode::KeyD),
+        (Increase(ITEM_RELEASE), KeyCode::KeyF),
+        (Decrease(ITEM_FREQ), KeyCode::KeyC),
+        (Increase(ITEM_FREQ), KeyCode::KeyV),
+        (Toggle(ITEM_CRUNCH), KeyCode::KeyT),
+        (Decrease(ITEM_CRUNCH), KeyCode::KeyY),
+        (Increase(ITEM_CRUNCH), KeyCode::KeyU),
+        (Toggle(ITEM_DRIVE), KeyCode::KeyG),
+        (Decrease(ITEM_DRIVE), KeyCode::KeyH),
+        (Increase(ITEM_DRIVE), KeyCode::KeyJ),
+        (Toggle(ITEM_VIBRATO), KeyCode::KeyT),
+        (Decrease(ITEM_VIBRATO), KeyCode::KeyY),
+        (Increase(ITEM_VIBRATO), KeyCode::KeyU),
+        (Decrease(ITEM_VIB_RATE), KeyCode::KeyQ),
+        (Increase(ITEM_VIB_RATE), KeyCode::KeyW),
+        (Decrease(ITEM_VIB_DELAY), KeyCode::KeyA),
+        (Increase(ITEM_VIB_DELAY), KeyCode::KeyS),
+        (Toggle(ITEM_TREMOLO), KeyCode::KeyG),
+        (Decrease(ITEM_TREMOLO), KeyCode::KeyH),
+        (Increase(ITEM_TREMOLO), KeyCode::KeyJ),
+        (Decrease(ITEM_TREM_RATE), KeyCode::KeyZ),
+        (Increase(ITEM_TREM_RATE), KeyCode::KeyX),
+        (Decrease(ITEM_TREM_DELAY), KeyCode::KeyE),
+        (Increase(ITEM_TREM_DELAY), KeyCode::KeyR),
+        (Toggle(ITEM_ARPEGGIO), KeyCode::KeyT),
+        (Decrease(ITEM_ARPEGGIO), KeyCode::KeyY),
+        (Increase(ITEM_ARPEGGIO), KeyCode::KeyU),
+        (Decrease(ITEM_ARP_TIME), KeyCode::KeyQ),
+        (Increase(ITEM_ARP_TIME), KeyCode::KeyW),
+        (Toggle(ITEM_REPEAT), KeyCode::KeyG),
+        (Decrease(ITEM_REPEAT), KeyCode::KeyH),
+        (Increase(ITEM_REPEAT), KeyCode::KeyJ),
+        (Toggle(ITEM_SNAP), KeyCode::KeyT),
+        (Decrease(ITEM_SNAP), KeyCode::KeyY),
+        (Increase(ITEM_SNAP), KeyCode::KeyU),
+        (Decrease(ITEM_ROOT), KeyCode::KeyQ),
+        (Increase(ITEM_ROOT), KeyCode::KeyW),
+        (Decrease(ITEM_TUNING), KeyCode::KeyA),
+        (Increase(ITEM_TUNING), KeyCode::KeyS),
+        (Oscillator(OscillatorType::Sine), KeyCode::KeyI),
+        (Oscillator(OscillatorType::Triangle), KeyCode::KeyO),
+        (Oscillator(OscillatorType::Saw), KeyCode::KeyP),
+        (Oscillator(OscillatorType::Square), KeyCode::KeyK),
+        (Oscillator(OscillatorType::Noise), KeyCode::KeyL),
+        (Duty(DutyCycle::Half), KeyCode::KeyB),
+        (Duty(DutyCycle::Third), KeyCode::KeyN),
+        (Duty(DutyCycle::Quarter), KeyCode::KeyM),
+        (Duty(DutyCycle::Eight), KeyCode::Comma),
+        (Play, KeyCode::Space),
+        (NextPage, KeyCode::Tab),
+        (KeyboardMode, KeyCode::Backquote),
+        (WaveformMode, KeyCode::Backslash),
+        (PrevRegister, KeyCode::BracketLeft),
+        (NextRegister, KeyCode::BracketRight),
+        (PrevTheme, KeyCode::ArrowLeft),
+        (NextTheme, KeyCode::ArrowRight),
+        (Help, KeyCode::F1),
+        (RestoreDeleted, KeyCode::F2),
+        (EditName, KeyCode::Enter),
+        (KeyBindings, KeyCode::F3),
+        (ThemeEditor, KeyCode::F4),
+        (ExportAll, KeyCode::F5),
+        (ExportSprite, KeyCode::F6),
+        (NextFormat, KeyCode::F7),
+        (NextRate, KeyCode::F8),
+        (ExportCurrent, KeyCode::F9),
+        (ExportInstrument, KeyCode::F10),
+        (ExportVariations, KeyCode::F11),
+        (Normalise, KeyCode::F12),
+    ];
+    let digits = [
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+        KeyCode::Digit0,
+    ];
+    bindings.extend(
+        digits
+            .into_iter()
+            .enumerate()
+            .map(|(slot, key)| (SaveSlot(slot), key)),
+    );
+    bindings.push((Quit, KeyCode::Escape));
+    bindings
+}
+
+/// Which key triggers each [Action]
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyMap {
+    bindings: IndexMap<Action, KeyCode>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        KeyMap {
+            bindings: default_bindings().into_iter().collect(),
+        }
+    }
+}
+
+impl KeyMap {
+    /// Default bindings changed by `overrides`, a map of action names to key names
+    pub fn new(overrides: &BTreeMap<String, String>) -> Self {
+        let mut keys = KeyMap::default();
+        for (action_name, key_name) in overrides {
+            let action = keys
+                .bindings
+                .keys()
+                .find(|action| action.name() == *action_name)
+                .copied();
+            match (action, key_from_name(key_name)) {
+                (Some(action), Some(key)) => keys.bind(action, key),
+                (None, _) => eprintln!("unknown action in key bindings: {action_name}"),
+                (_, None) => eprintln!("unknown key in key bindings: {key_name}"),
+            }
+        }
+        keys
+    }
+
+    /// Bindings that differ from the defaults, to store in settings
+    pub fn overrides(&self) -> BTreeMap<String, String> {
+        let defaults = KeyMap::default();
+        self.bindings
+            .iter()
+            .filter(|(action, key)| defaults.key(**action) != Some(**key))
+            .filter_map(|(action, key)| Some((action.name(), key_name(*key)?.to_string())))
+            .collect()
+    }
+
+    pub fn bindings(&self) -> impl Iterator<Item = (Action, KeyCode)> + '_ {
+        self.bindings.iter().map(|(action, key)| (*action, *key))
+    }
+
+    pub fn key(&self, action: Action) -> Option<KeyCode> {
+        self.bindings.get(&action).copied()
+    }
+
+    pub fn bind(&mut self, action: Action, key: KeyCode) {
+        self.bindings.insert(action, key);
+    }
+
+    pub fn reset(&mut self, action: Action) {
+        if let Some(key) = KeyMap::default().key(action) {
+            self.bind(action, key);
+        }
+    }
+
+    /// Actions bound to `key`
+    pub fn actions(&self, key: KeyCode) -> Vec<Action> {
+        self.bindings
+            .iter()
+            .filter(|(_, bound)| **bound == key)
+            .map(|(action, _)| *action)
+            .collect()
+    }
+
+    pub fn is(&self, key: KeyCode, action: Action) -> bool {
+        self.key(action) == Some(key)
+    }
+
+    /// Short label for buttons and hints, such as `Q` or `F5`
+    pub fn label(&self, action: Action) -> &'static str {
+        self.key(action).and_then(key_label).unwrap_or("?")
+    }
+
+    /// Readable name of the key, such as `Q` or `Space`
+    pub fn key_name(&self, action: Action) -> &'static str {
+        self.key(action)
+            .and_then(key_name)
+            .map(|name| {
+                name.strip_prefix("Key")
+                    .or_else(|| name.strip_prefix("Digit"))
+                    .unwrap_or(name)
+            })
+            .unwrap_or("?")
+    }
+}
+
+/// Key, name used in settings (matching winit) and short label
+const KEY_NAMES: [(KeyCode, &str, &str); 79] = [
+    (KeyCode::KeyA, "KeyA", "A"),
+    (KeyCode::KeyB, "KeyB", "B"),
+    (KeyCode::KeyC, "KeyC", "C"),
+    (KeyCode::KeyD, "KeyD", "D"),
+    (KeyCode::KeyE, "KeyE", "E"),
+    (KeyCode::KeyF, "KeyF", "F"),
+    (KeyCode::KeyG, "KeyG", "G"),
+    (KeyCode::KeyH, "KeyH", "H"),
+    (KeyCode::KeyI, "KeyI", "I"),
+    (KeyCode::KeyJ, "KeyJ", "J"),
+    (KeyCode::KeyK, "KeyK", "K"),
+    (KeyCode::KeyL, "KeyL", "L"),
+    (KeyCode::KeyM, "KeyM", "M"),
+    (KeyCode::KeyN, "KeyN", "N"),
+    (KeyCode::KeyO, "KeyO", "O"),
+    (KeyCode::KeyP, "KeyP", "P"),
+    (KeyCode::KeyQ, "KeyQ", "Q"),
+    (KeyCode::KeyR, "KeyR", "R"),
+    (KeyCode::KeyS, "KeyS", "S"),
+    (KeyCode::KeyT, "KeyT", "T"),
+    (KeyCode::KeyU, "KeyU", "U"),
+    (KeyCode::KeyV, "KeyV", "V"),
+    (KeyCode::KeyW, "KeyW", "W"),
+    (KeyCode::KeyX, "KeyX", "X"),
+    (KeyCode::KeyY, "KeyY", "Y"),
+    (KeyCode::KeyZ, "KeyZ", "Z"),
+    (KeyCode::Digit0, "Digit0", "0"),
+    (KeyCode::Digit1, "Digit1", "1"),
+    (KeyCode::Digit2, "Digit2", "2"),
+    (KeyCode::Digit3, "Digit3", "3"),
+    (KeyCode::Digit4, "Digit4", "4"),
+    (KeyCode::Digit5, "Digit5", "5"),
+    (KeyCode::Digit6, "Digit6", "6"),
+    (KeyCode::Digit7, "Digit7", "7"),
+    (KeyCode::Digit8, "Digit8", "8"),
+    (KeyCode::Digit9, "Digit9", "9"),
+    (KeyCode::F1, "F1", "F1"),
+    (KeyCode::F2, "F2", "F2"),
+    (KeyCode::F3, "F3", "F3"),
+    (KeyCode::F4, "F4", "F4"),
+    (KeyCode::F5, "F5", "F5"),
+    (KeyCode::F6, "F6", "F6"),
+    (KeyCode::F7, "F7", "F7"),
+    (KeyCode::F8, "F8", "F8"),
+    (KeyCode::F9, "F9", "F9"),
+    (KeyCode::F10, "F10", "F10"),
+    (KeyCode::F11, "F11", "F11"),
+    (KeyCode::F12, "F12", "F12"),
+    (KeyCode::Space, "Space", "SPC"),
+    (KeyCode::Tab, "Tab", "TAB"),
+    (KeyCode::Enter, "Enter", "ENT"),
+    (KeyCode::Escape, "Escape", "ESC"),
+    (KeyCode::Backspace, "Backspace", "BSP"),
+    (KeyCode::Delete, "Delete", "DEL"),
+    (KeyCode::Insert, "Insert", "INS"),
+    (KeyCode::Home, "Home", "HOM"),
+    (KeyCode::End, "End", "END"),
+    (KeyCode::PageUp, "PageUp", "PGU"),
+    (KeyCode::PageDown, "PageDown", "PGD"),
+    (KeyCode::ArrowUp, "ArrowUp", "UP"),
+    (KeyCode::ArrowDown, "ArrowDown", "DN"),
+    (KeyCode::ArrowLeft, "ArrowLeft", "LT"),
+    (KeyCode::ArrowRight, "ArrowRight", "RT"),
+    (KeyCode::Minus, "Minus", "-"),
+    (KeyCode::Equal, "Equal", "="),
+    (KeyCode::BracketLeft, "BracketLeft", "["),
+    (KeyCode::BracketRight, "BracketRight", "]"),
+    (KeyCode::Backslash, "Backslash", "\\"),
+    (KeyCode::Semicolon, "Semicolon", ";"),
+    (KeyCode::Quote, "Quote", "'"),
+    (KeyCode::Backquote, "Backquote", "`"),
+    (KeyCode::Comma, "Comma", ","),
+    (KeyCode::Period, "Period", "."),
+    (KeyCode::Slash, "Slash", "/"),
+    (KeyCode::NumpadAdd, "NumpadAdd", "N+"),
+    (KeyCode::NumpadSubtract, "NumpadSubtract", "N-"),
+    (KeyCode::NumpadMultiply, "NumpadMultiply", "N*"),
+    (KeyCode::NumpadDivide, "NumpadDivide", "N/"),
+    (KeyCode::NumpadEnter, "NumpadEnter", "NEN"),
+];
+
+/// Keys that can be bound, modifiers and unusual keys can't be
+pub fn is_bindable(key: KeyCode) -> bool {
+    key_name(key).is_some()
+}
+
+fn key_name(key: KeyCode) -> Option<&'static str> {
+    KEY_NAMES
+        .iter()
+        .find(|(code, _, _)| *code == key)
+        .map(|(_, name, _)| *name)
+}
+
+fn key_label(key: KeyCode) -> Option<&'static str> {
+    KEY_NAMES
+        .iter()
+        .find(|(code, _, _)| *code == key)
+        .map(|(_, _, label)| *label)
+}
+
+fn key_from_name(name: &str) -> Option<KeyCode> {
+    KEY_NAMES
+        .iter()
+        .find(|(_, key_name, _)| *key_name == name)
+        .map(|(code, _, _)| *code)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn overrides_round_trip() {
+        let mut keys = KeyMap::default();
+        assert!(keys.overrides().is_empty());
+        keys.bind(Action::Play, KeyCode::KeyB);
+        keys.bind(Action::SaveSlot(3), KeyCode::F12);
+        let overrides = keys.overrides();
+        assert_eq!(overrides.len(), 2);
+        assert_eq!(overrides["Play"], "KeyB");
+        let loaded = KeyMap::new(&overrides);
+        assert_eq!(loaded, keys);
+        assert_eq!(loaded.overrides(), overrides);
+    }
+
+    #[test]
+    fn skips_unknown_names() {
+        let overrides = BTreeMap::from([
+            (String::from("Dance"), String::from("KeyB")),
+            (String::from("Play"), String::from("Banana")),
+            (String::from("Quit"), String::from("KeyB")),
+        ]);
+        let keys = KeyMap::new(&overrides);
+        let mut expected = KeyMap::default();
+        expected.bind(Action::Quit, KeyCode::KeyB);
+        assert_eq!(keys, expected);
+        assert_eq!(keys.key(Action::Play), KeyMap::default().key(Action::Play));
+    }
+}
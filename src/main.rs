@@ -1,17 +1,41 @@
 #![windows_subsystem = "windows"]
 
 mod audio;
+mod cli;
 mod controller;
+mod export;
+mod help;
+mod instrument;
+mod key_bindings;
+mod keymap;
 mod main_scene;
+mod meter;
+mod modulation;
+mod name_editor;
+mod notes;
+mod recovery;
+mod render;
+mod repeat;
+mod sequence;
 mod settings;
 mod theme;
+mod theme_editor;
+mod toast;
 mod ui;
+mod variation;
 mod waveform;
+mod worker;
 
 use crate::audio::Audio;
 use crate::controller::Controller;
+use crate::help::{HelpScene, HelpSetup};
+use crate::key_bindings::{KeyBindingsScene, KeyBindingsSetup};
+use crate::keymap::KeyMap;
 use crate::main_scene::MainScene;
+use crate::name_editor::{NameEdit, NameEditorScene, NameEditorSetup};
 use crate::settings::Settings;
+use crate::theme::{load_themes, Theme};
+use crate::theme_editor::{ThemeEditorScene, ThemeEditorSetup};
 use anyhow::Result;
 use log::LevelFilter;
 use pixels_graphics_lib::prelude::*;
@@ -28,16 +52,34 @@ fn main() -> Result<()> {
         .format_level(false)
         .init();
 
+    let mut args = std::env::args().skip(1);
+    if let Some(arg) = args.next().filter(|arg| cli::is_command(arg)) {
+        return cli::run(&arg, args.collect());
+    }
+
     let window_prefs = WindowPreferences::new("app", "emmabritton", "usfx_tester", 2)?;
     let app_prefs: AppPrefs<Settings> =
         AppPrefs::new("app", "emmabritton", "usfx_tester", Settings::default)?;
-    let system = Box::new(MainScene::new(app_prefs));
+    let config_dir = get_pref_dir("app", "emmabritton", "usfx_tester")
+        .map_err(|err| eprintln!("config dir unavailable: {err:?}"))
+        .ok();
+    let recovered = config_dir.as_ref().and_then(|dir| {
+        recovery::install(dir.clone(), &app_prefs.data);
+        recovery::load(&app_prefs.data)
+    });
+    let themes = load_themes(config_dir.as_deref());
+    let system = Box::new(MainScene::new(app_prefs, themes, recovered));
     run_scenes(
         WIDTH,
         HEIGHT,
         "USFX Test",
         Some(window_prefs),
-        |_style, _list, _key| {},
+        |_style, scenes, new_scene| match new_scene {
+            SN::ThemeEditor(setup) => scenes.push(Box::new(ThemeEditorScene::new(*setup))),
+            SN::KeyBindings(setup) => scenes.push(Box::new(KeyBindingsScene::new(*setup))),
+            SN::Help(setup) => scenes.push(Box::new(HelpScene::new(*setup))),
+            SN::NameEditor(setup) => scenes.push(Box::new(NameEditorScene::new(*setup))),
+        },
         system,
         Options::default(),
         empty_pre_post(),
@@ -45,10 +87,20 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-//unused for this app but needed by scene system
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum SR {}
+/// Results returned to the main scene
+#[derive(Debug, Clone, PartialEq)]
+enum SR {
+    /// Edited theme to save as a custom theme
+    Theme(Theme),
+    Keys(KeyMap),
+    Name(NameEdit),
+}
 
-//unused for this app but needed by scene system
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum SN {}
+/// Scenes that can be opened from the main scene
+#[derive(Debug, Clone, PartialEq)]
+enum SN {
+    ThemeEditor(Box<ThemeEditorSetup>),
+    KeyBindings(Box<KeyBindingsSetup>),
+    Help(Box<HelpSetup>),
+    NameEditor(Box<NameEditorSetup>),
+}
@@ -1,178 +1,735 @@
 use crate::audio::SAMPLE_RATE;
-use crate::theme::{themes, Theme};
-use crate::waveform::Waveform;
+use crate::controller::InputMode;
+use crate::export::{export_all, export_sound, export_sprite, sanitise_name};
+use crate::help::HelpSetup;
+use crate::instrument::export_instrument;
+use crate::key_bindings::KeyBindingsSetup;
+use crate::keymap::{Action, KeyMap};
+use crate::name_editor::{NameEdit, NameEditorSetup};
+use crate::recovery;
+use crate::repeat::KeyRepeat;
+use crate::theme::{find_theme, merge_themes, saved_themes, Theme, ThemeData};
+use crate::theme_editor::ThemeEditorSetup;
+use crate::toast::Toasts;
+use crate::ui::draw_prompt;
+use crate::variation::{create_variants, export_variants, preview};
+use crate::worker::WaveformWorker;
 use crate::*;
+use anyhow::Result;
 use pixels_graphics_lib::buffer_graphics_lib::Graphics;
 use pixels_graphics_lib::prelude::SceneUpdateResult::Nothing;
 use pixels_graphics_lib::prelude::*;
-use pixels_graphics_lib::scenes::SceneUpdateResult::Pop;
-use usfx::{Mixer, Sample};
+use pixels_graphics_lib::scenes::SceneUpdateResult::{Pop, Push};
+use std::path::PathBuf;
+use std::thread;
+
+/// Seconds to wait after the last change before saving settings
+const AUTOSAVE_DELAY: f64 = 1.0;
+/// Seconds before another action that overwrites saved sounds is allowed
+const DESTRUCTIVE_LOCKOUT: f64 = 0.5;
+
+/// Slot change waiting for the user to confirm it
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Confirm {
+    Overwrite(usize),
+    Delete(usize),
+}
+
+impl Confirm {
+    /// Y, or the same keys that asked for the change, other modifiers (such as shift to load)
+    /// don't count
+    fn is_confirmed_by(
+        &self,
+        keys: &KeyMap,
+        key: KeyCode,
+        shift_pressed: bool,
+        ctrl_pressed: bool,
+        alt_pressed: bool,
+    ) -> bool {
+        let (idx, delete) = match self {
+            Confirm::Overwrite(idx) => (*idx, false),
+            Confirm::Delete(idx) => (*idx, true),
+        };
+        key == KeyCode::KeyY
+            || (keys.is(key, Action::SaveSlot(idx))
+                && ctrl_pressed == delete
+                && !shift_pressed
+                && !alt_pressed)
+    }
+}
 
 pub struct MainScene {
     controller: Controller,
     result: SceneUpdateResult<SR, SN>,
-    next_input: f64,
+    repeat: KeyRepeat,
+    /// Seconds until another destructive action is allowed
+    lockout: f64,
     prefs: AppPrefs<Settings>,
+    /// Built in themes and those from the themes file, before custom themes from settings
+    file_themes: Vec<Theme>,
     themes: Vec<Theme>,
+    /// Index of the current theme in `themes`, settings store its name
+    theme: usize,
+    worker: WaveformWorker,
+    /// Seconds until settings are saved, if there are unsaved changes
+    autosave: Option<f64>,
+    /// Work left behind by a crash, waiting for the user to restore or discard it
+    recovered: Option<Settings>,
+    toasts: Toasts,
+    confirm: Option<Confirm>,
+    /// The last render failed and has been requested again
+    render_retried: bool,
 }
 
 impl MainScene {
-    pub fn new(mut prefs: AppPrefs<Settings>) -> MainScene {
+    pub fn new(
+        mut prefs: AppPrefs<Settings>,
+        mut themes: Vec<Theme>,
+        recovered: Option<Settings>,
+    ) -> MainScene {
         let mut audio = Audio::new();
         audio.run();
-        let themes = themes();
-        prefs.data.theme = prefs.data.theme.min(themes.len() - 1);
+        let file_themes = themes.clone();
+        merge_themes(&mut themes, saved_themes(&prefs.data.themes));
+        let theme = find_theme(&themes, &prefs.data.theme);
+        let mut controller = Controller::new(audio, &themes[theme]);
+        controller.max_length = prefs.data.max_length;
+        controller.set_keys(KeyMap::new(&prefs.data.keys), &themes[theme]);
+        prefs.data.clear_generated_names();
+        if let Some(sound) = &prefs.data.current {
+            controller.load(sound);
+        }
         MainScene {
-            controller: Controller::new(audio, &themes[prefs.data.theme]),
+            controller,
             result: Nothing,
-            next_input: 0.0,
+            repeat: KeyRepeat::new(prefs.data.repeat),
+            lockout: 0.0,
             prefs,
+            file_themes,
             themes,
+            theme,
+            worker: WaveformWorker::new(),
+            autosave: None,
+            recovered,
+            toasts: Toasts::default(),
+            confirm: None,
+            render_retried: false,
+        }
+    }
+}
+
+/// Covers both quitting with Escape and closing the window, everything is saved so the recovery
+/// file isn't needed, unless it hasn't been restored or discarded yet
+impl Drop for MainScene {
+    fn drop(&mut self) {
+        self.save_prefs();
+        if !thread::panicking() && self.recovered.is_none() {
+            recovery::discard();
         }
     }
 }
 
 impl MainScene {
+    fn save_prefs(&mut self) {
+        self.prefs.data.current = Some(self.controller.create_save_data());
+        self.prefs.save();
+        self.autosave = None;
+    }
+
+    /// Save settings soon, restarting the countdown if it's already running
+    fn settings_changed(&mut self) {
+        self.autosave = Some(AUTOSAVE_DELAY);
+        self.update_recovery();
+    }
+
+    fn update_recovery(&mut self) {
+        let mut settings = self.prefs.data.clone();
+        settings.current = Some(self.controller.create_save_data());
+        recovery::update(&settings);
+    }
+
+    /// Replace settings and the current sound with those recovered after a crash
+    fn restore(&mut self, settings: Settings) {
+        self.prefs.data = settings;
+        self.prefs.data.clear_generated_names();
+        self.themes = self.file_themes.clone();
+        merge_themes(&mut self.themes, saved_themes(&self.prefs.data.themes));
+        self.theme = find_theme(&self.themes, &self.prefs.data.theme);
+        self.controller.max_length = self.prefs.data.max_length;
+        self.controller
+            .set_keys(KeyMap::new(&self.prefs.data.keys), &self.themes[self.theme]);
+        self.controller.on_theme_change(&self.themes[self.theme]);
+        self.repeat = KeyRepeat::new(self.prefs.data.repeat);
+        if let Some(sound) = &self.prefs.data.current {
+            self.controller.load(sound);
+        }
+        self.settings_changed();
+    }
+
+    fn set_theme(&mut self, theme: usize) {
+        self.theme = theme;
+        self.prefs.data.theme = self.themes[theme].name.clone();
+        self.controller.on_theme_change(&self.themes[theme]);
+        self.settings_changed();
+    }
+
+    fn open_theme_editor(&mut self) {
+        let settings = &self.prefs.data;
+        let setup = ThemeEditorSetup {
+            themes: self.themes.clone(),
+            active: self.theme,
+            sound: self.controller.create_save_data(),
+            saves: settings.saved.clone(),
+            export: settings.export,
+            target: settings.normalise_target,
+            max_length: settings.max_length,
+        };
+        self.result = Push(false, SN::ThemeEditor(Box::new(setup)));
+    }
+
+    /// Save `theme` in settings and switch to it
+    ///
+    /// Custom themes are replaced, other themes are saved as a new custom theme
+    fn save_theme(&mut self, mut theme: Theme) {
+        let saved = &mut self.prefs.data.themes;
+        match saved
+            .iter_mut()
+            .find(|saved| saved.name.eq_ignore_ascii_case(&theme.name))
+        {
+            Some(existing) => *existing = ThemeData::from(&theme),
+            None => {
+                theme.name = (1..)
+                    .map(|i| format!("Custom {i}"))
+                    .find(|name| {
+                        !self
+                            .themes
+                            .iter()
+                            .any(|theme| theme.name.eq_ignore_ascii_case(name))
+                    })
+                    .unwrap_or_default();
+                saved.push(ThemeData::from(&theme));
+            }
+        }
+        let name = theme.name.clone();
+        merge_themes(&mut self.themes, vec![theme]);
+        self.set_theme(find_theme(&self.themes, &name));
+        self.toasts.info(format!("Saved theme {name}"));
+    }
+
+    /// Handle actions that affect settings or open other scenes, returns true if `action` was
+    /// handled, otherwise it's passed on to the controller
+    fn handle_action(
+        &mut self,
+        action: Action,
+        shift_pressed: bool,
+        ctrl_pressed: bool,
+        alt_pressed: bool,
+    ) -> bool {
+        match action {
+            Action::PrevTheme => {
+                self.set_theme(self.theme.saturating_sub(1));
+            }
+            Action::NextTheme => {
+                self.set_theme((self.theme + 1).min(self.themes.len() - 1));
+            }
+            Action::KeyBindings => self.open_key_bindings(),
+            Action::Help => self.open_help(),
+            Action::ThemeEditor => self.open_theme_editor(),
+            Action::ExportAll => self.export_all(),
+            Action::ExportSprite => self.export_sprite(),
+            Action::NextFormat => {
+                let export = &mut self.prefs.data.export;
+                if shift_pressed {
+                    export.next_source();
+                } else {
+                    export.format = export.format.next();
+                }
+                self.settings_changed();
+            }
+            Action::NextRate => {
+                self.prefs.data.export.next_rate();
+                self.settings_changed();
+            }
+            Action::ExportCurrent => self.export_current(),
+            Action::ExportInstrument => self.export_instrument(),
+            Action::ExportVariations => self.export_variations(),
+            Action::Normalise => {
+                let target = self.prefs.data.normalise_target;
+                let max_length = self.prefs.data.max_length;
+                if ctrl_pressed {
+                    self.prefs.data.normalise_target = target.toggle();
+                    self.settings_changed();
+                } else if shift_pressed {
+                    self.prefs
+                        .data
+                        .saved
+                        .iter_mut()
+                        .flatten()
+                        .for_each(|sound| sound.normalise(target, max_length));
+                    self.settings_changed();
+                    self.toasts.info("Normalised saved sounds");
+                } else {
+                    self.controller.normalise(target);
+                }
+            }
+            Action::RestoreDeleted => self.restore_deleted(),
+            Action::EditName => self.open_name_editor(None),
+            Action::SaveSlot(idx) => {
+                let occupied = self.prefs.data.saved[idx].is_some();
+                let confirm = self.prefs.data.confirm_overwrite && occupied;
+                if alt_pressed {
+                    if occupied {
+                        self.open_name_editor(Some(idx));
+                    } else {
+                        self.toasts.info(format!("Slot {} empty", slot_number(idx)));
+                    }
+                } else if ctrl_pressed {
+                    if confirm {
+                        self.confirm = Some(Confirm::Delete(idx));
+                    } else {
+                        self.delete_sound(idx);
+                    }
+                } else if shift_pressed {
+                    self.load_sound(idx);
+                } else if confirm {
+                    self.confirm = Some(Confirm::Overwrite(idx));
+                } else {
+                    self.save_sound(idx);
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn open_key_bindings(&mut self) {
+        let setup = KeyBindingsSetup {
+            keys: self.controller.keys.clone(),
+            theme: self.themes[self.theme].clone(),
+            pages: self
+                .controller
+                .items
+                .keys()
+                .map(|item| (item.name, item.page))
+                .collect(),
+        };
+        self.result = Push(false, SN::KeyBindings(Box::new(setup)));
+    }
+
+    /// Edit the name and notes of the sound in `slot`, or the current sound if None
+    fn open_name_editor(&mut self, slot: Option<usize>) {
+        let sound = match slot {
+            Some(idx) => self.prefs.data.saved[idx].clone(),
+            None => Some(self.controller.create_save_data()),
+        };
+        if let Some(sound) = sound {
+            let setup = NameEditorSetup {
+                edit: NameEdit {
+                    slot,
+                    name: sound.name.clone(),
+                    notes: sound.notes.clone(),
+                },
+                summary: sound.summary(),
+                theme: self.themes[self.theme].clone(),
+            };
+            self.result = Push(false, SN::NameEditor(Box::new(setup)));
+        }
+    }
+
+    fn rename(&mut self, edit: NameEdit) {
+        match edit.slot {
+            Some(idx) => {
+                if let Some(sound) = &mut self.prefs.data.saved[idx] {
+                    sound.name = edit.name;
+                    sound.notes = edit.notes;
+                    self.toasts
+                        .info(format!("Renamed slot {}", slot_number(idx)));
+                }
+            }
+            None => {
+                self.controller.name = edit.name;
+                self.controller.notes = edit.notes;
+                self.toasts.info("Renamed current sound");
+            }
+        }
+        self.settings_changed();
+    }
+
+    fn open_help(&mut self) {
+        let setup = HelpSetup {
+            keys: self.controller.keys.clone(),
+            theme: self.themes[self.theme].clone(),
+            items: self.controller.items.keys().cloned().collect(),
+        };
+        self.result = Push(false, SN::Help(Box::new(setup)));
+    }
+
     fn save_sound(&mut self, idx: usize) {
-        self.prefs.data.saved[idx] = Some(self.controller.create_save_data());
+        let sound = self.controller.create_save_data();
+        if let Some(old) = self.prefs.data.saved[idx].replace(sound) {
+            self.prefs.data.trash(idx, old);
+        }
+        self.settings_changed();
+        self.toasts
+            .info(format!("Saved to slot {}", slot_number(idx)));
     }
 
     fn load_sound(&mut self, idx: usize) {
-        if let Some(sound) = &self.prefs.data.saved[idx] {
-            self.controller.load(sound);
+        match &self.prefs.data.saved[idx] {
+            Some(sound) => {
+                self.controller.load(sound);
+                self.toasts
+                    .info(format!("Loaded slot {}", slot_number(idx)));
+            }
+            None => self.toasts.info(format!("Slot {} empty", slot_number(idx))),
         }
     }
 
     fn delete_sound(&mut self, idx: usize) {
-        self.prefs.data.saved[idx] = None;
+        if let Some(sound) = self.prefs.data.saved[idx].take() {
+            self.prefs.data.trash(idx, sound);
+            self.settings_changed();
+            self.toasts
+                .info(format!("Deleted slot {}", slot_number(idx)));
+        } else {
+            self.toasts.info(format!("Slot {} empty", slot_number(idx)));
+        }
+    }
+
+    /// Put the last deleted or overwritten sound back in its slot, or the first empty slot
+    fn restore_deleted(&mut self) {
+        let settings = &mut self.prefs.data;
+        let Some(trashed) = settings.trash.pop() else {
+            self.toasts.info("Nothing to restore");
+            return;
+        };
+        // the slot comes from the settings file so may be out of range
+        let slot = match settings.saved.get(trashed.slot) {
+            Some(None) => Some(trashed.slot),
+            _ => settings.saved.iter().position(Option::is_none),
+        };
+        match slot {
+            Some(slot) => {
+                settings.saved[slot] = Some(trashed.sound);
+                self.settings_changed();
+                self.toasts
+                    .info(format!("Restored to slot {}", slot_number(slot)));
+            }
+            None => {
+                settings.trash.push(trashed);
+                self.toasts.error("No empty slot to restore to");
+            }
+        }
+    }
+
+    /// Handle a key while a slot change is waiting to be confirmed, pressing the same keys again
+    /// or Y confirms, anything else cancels
+    fn confirm_key_pressed(
+        &mut self,
+        confirm: Confirm,
+        key: KeyCode,
+        shift_pressed: bool,
+        ctrl_pressed: bool,
+        alt_pressed: bool,
+    ) {
+        let keys = &self.controller.keys;
+        if !confirm.is_confirmed_by(keys, key, shift_pressed, ctrl_pressed, alt_pressed) {
+            return;
+        }
+        match confirm {
+            Confirm::Overwrite(idx) => self.save_sound(idx),
+            Confirm::Delete(idx) => self.delete_sound(idx),
+        }
+    }
+
+    /// Show the file written by an export, or why it failed
+    fn export_finished(&mut self, result: Result<PathBuf>) {
+        match result {
+            Ok(path) => self.toasts.info(format!(
+                "Exported {}",
+                path.file_name().unwrap_or_default().to_string_lossy()
+            )),
+            Err(err) => {
+                eprintln!("export failed: {err:?}");
+                self.toasts.error(format!("Export failed: {err}"));
+            }
+        }
+    }
+
+    fn export_all(&mut self) {
+        let settings = &self.prefs.data;
+        let dir = settings.export_dir();
+        match export_all(&settings.saved, &settings.export, settings.max_length, &dir) {
+            Ok(manifest) => self.toasts.info(format!(
+                "Exported {} sounds to {}",
+                manifest.len(),
+                dir.display()
+            )),
+            Err(err) => {
+                eprintln!("export failed: {err:?}");
+                self.toasts.error(format!("Export failed: {err}"));
+            }
+        }
+    }
+
+    fn export_current(&mut self) {
+        let settings = &self.prefs.data;
+        let sound = self.controller.create_save_data();
+        let result = export_sound(
+            &sound,
+            &settings.export,
+            settings.max_length,
+            &settings.export_dir(),
+        );
+        self.export_finished(result);
+    }
+
+    fn export_instrument(&mut self) {
+        let settings = &self.prefs.data;
+        let sound = self.controller.create_save_data();
+        let result = export_instrument(
+            &sound,
+            &sanitise_name(&sound.display_name()),
+            settings.export.sample_rate,
+            settings.max_length,
+            &settings.export_dir(),
+        );
+        self.export_finished(result);
+    }
+
+    /// Create, play and export variations of the current sound
+    fn export_variations(&mut self) {
+        let settings = &self.prefs.data;
+        let sound = self.controller.create_save_data();
+        let variants = create_variants(&sound, &settings.variation);
+        self.controller
+            .audio
+            .play(preview(&variants, SAMPLE_RATE, settings.max_length));
+        let result = export_variants(
+            &sound,
+            &variants,
+            &settings.variation,
+            &settings.export,
+            settings.max_length,
+            &settings.export_dir(),
+        );
+        self.export_finished(result);
+    }
+
+    fn export_sprite(&mut self) {
+        let settings = &self.prefs.data;
+        let slots: Vec<usize> = (0..settings.saved.len()).collect();
+        let result = export_sprite(
+            &settings.saved,
+            &slots,
+            settings.sprite_gap,
+            &settings.export,
+            settings.max_length,
+            &settings.export_dir(),
+        );
+        self.export_finished(result);
     }
 }
 
 impl Scene<SR, SN> for MainScene {
     fn render(&self, graphics: &mut Graphics, _: &MouseData, _: &FxHashSet<KeyCode>) {
-        let theme = &self.themes[self.prefs.data.theme];
+        let theme = &self.themes[self.theme];
         graphics.clear(theme.background);
         self.controller.render(
             graphics,
-            theme,
-            self.prefs.data.theme,
+            &self.themes,
+            self.theme,
             &self.prefs.data.saved,
+            &self.prefs.data.export,
+            &self.prefs.data.normalise_target,
         );
+        self.toasts.render(graphics, theme);
+        if self.recovered.is_some() {
+            draw_prompt(
+                graphics,
+                theme,
+                &[
+                    "RECOVER UNSAVED WORK FROM",
+                    "BEFORE THE LAST CRASH?",
+                    "",
+                    "Y RESTORE   N DISCARD",
+                ],
+            );
+        }
+        if let Some(confirm) = self.confirm {
+            let keys = &self.controller.keys;
+            let restore = format!(
+                "IT CAN BE RESTORED WITH {}",
+                keys.label(Action::RestoreDeleted)
+            );
+            let (question, again) = match confirm {
+                Confirm::Overwrite(idx) => (
+                    format!("SAVE OVER SLOT {}?", slot_number(idx)),
+                    keys.label(Action::SaveSlot(idx)).to_string(),
+                ),
+                Confirm::Delete(idx) => (
+                    format!("DELETE SLOT {}?", slot_number(idx)),
+                    format!("CTRL+{}", keys.label(Action::SaveSlot(idx))),
+                ),
+            };
+            let answer = format!("{again} OR Y YES   N NO");
+            draw_prompt(graphics, theme, &[&question, &restore, "", &answer]);
+        }
     }
 
     fn on_key_down(&mut self, key: KeyCode, _: &MouseData, held_keys: &FxHashSet<KeyCode>) {
         if is_modifier(key) {
             return;
         }
-        if self.next_input <= 0.0 {
-            self.next_input = 0.5;
-            if key == KeyCode::ArrowLeft {
-                self.prefs.data.theme = self.prefs.data.theme.saturating_sub(1);
-                self.controller
-                    .on_theme_change(&self.themes[self.prefs.data.theme]);
-                return;
+        if self.recovered.is_some() {
+            match key {
+                KeyCode::KeyY => {
+                    if let Some(settings) = self.recovered.take() {
+                        self.restore(settings);
+                    }
+                }
+                KeyCode::KeyN => self.recovered = None,
+                _ => return,
             }
-            if key == KeyCode::ArrowRight {
-                self.prefs.data.theme = (self.prefs.data.theme + 1).min(self.themes.len() - 1);
-                self.controller
-                    .on_theme_change(&self.themes[self.prefs.data.theme]);
+            recovery::discard();
+            return;
+        }
+        let (shift_pressed, ctrl_pressed, alt_pressed) = modifiers(held_keys);
+        if let Some(confirm) = self.confirm.take() {
+            self.confirm_key_pressed(confirm, key, shift_pressed, ctrl_pressed, alt_pressed);
+            return;
+        }
+        let actions = self.controller.keys.actions(key);
+        if actions.contains(&Action::Quit) {
+            //settings are saved when the scene is dropped
+            self.result = Pop(None);
+            return;
+        }
+        if actions.iter().any(Action::is_destructive) {
+            if self.lockout > 0.0 {
                 return;
             }
-            if matches!(
-                key,
-                KeyCode::Digit1
-                    | KeyCode::Digit2
-                    | KeyCode::Digit3
-                    | KeyCode::Digit4
-                    | KeyCode::Digit5
-                    | KeyCode::Digit6
-                    | KeyCode::Digit7
-                    | KeyCode::Digit8
-                    | KeyCode::Digit9
-                    | KeyCode::Digit0
-            ) {
-                let idx = func_key_idx(key);
-                if held_keys.contains(&KeyCode::ControlLeft)
-                    || held_keys.contains(&KeyCode::ControlRight)
-                {
-                    self.delete_sound(idx);
-                } else if held_keys.contains(&KeyCode::ShiftLeft)
-                    || held_keys.contains(&KeyCode::ShiftRight)
-                {
-                    self.load_sound(idx);
-                } else {
-                    self.save_sound(idx);
-                }
+            self.lockout = DESTRUCTIVE_LOCKOUT;
+        }
+        for action in &actions {
+            if self.handle_action(*action, shift_pressed, ctrl_pressed, alt_pressed) {
+                return;
             }
-            self.controller.key_pressed(
-                key,
-                held_keys.contains(&KeyCode::ShiftLeft) || held_keys.contains(&KeyCode::ShiftRight),
-                held_keys.contains(&KeyCode::ControlLeft)
-                    || held_keys.contains(&KeyCode::ControlRight),
-            );
         }
-        if key == KeyCode::Escape {
-            self.prefs.save();
-            self.result = Pop(None);
+        self.controller
+            .key_pressed(key, shift_pressed, ctrl_pressed);
+        if self.controller.input_mode == InputMode::Parameters
+            && actions.iter().any(Action::repeats)
+        {
+            self.repeat.press(key);
         }
     }
 
+    fn on_key_up(&mut self, key: KeyCode, _: &MouseData, _: &FxHashSet<KeyCode>) {
+        self.repeat.release(key);
+    }
+
     fn update(
         &mut self,
         timing: &Timing,
         _: &MouseData,
-        _: &FxHashSet<KeyCode>,
+        held_keys: &FxHashSet<KeyCode>,
         _: &Window,
     ) -> SceneUpdateResult<SR, SN> {
+        if let Some((key, count)) = self.repeat.update(timing.fixed_time_step) {
+            let (shift_pressed, ctrl_pressed, _) = modifiers(held_keys);
+            for _ in 0..count {
+                self.controller
+                    .key_pressed(key, shift_pressed, ctrl_pressed);
+            }
+        }
         if self.controller.has_changed {
             self.controller.has_changed = false;
-            let sample = self.controller.create_sample();
-            let data = convert_to_data(sample);
-            self.controller.waveform = Waveform::new(data, SAMPLE_RATE as usize, 334, 42);
+            let sound = self.controller.create_save_data();
+            let compare = self.controller.compare_sound().cloned();
+            self.controller.differences = compare
+                .as_ref()
+                .map(|compare| sound.differences(compare))
+                .unwrap_or_default();
+            let compare = compare.filter(|_| self.controller.compare_waveform.is_none());
+            self.worker.request(
+                sound,
+                compare,
+                self.controller.absolute_waveform,
+                self.controller.max_length,
+            );
+            self.controller.rendering = true;
+            self.update_recovery();
         }
-        self.next_input -= timing.fixed_time_step;
-        self.result.clone()
+        match self.worker.poll() {
+            Ok(Some(rendered)) => {
+                self.controller.waveform = rendered.waveform;
+                self.controller.levels = rendered.levels;
+                if rendered.compare.is_some() {
+                    self.controller.compare_waveform = rendered.compare;
+                }
+                self.controller.rendering = false;
+                self.render_retried = false;
+            }
+            Ok(None) => {}
+            Err(err) => {
+                eprintln!("rendering failed: {err:?}");
+                if self.render_retried {
+                    self.toasts.error(format!("Rendering failed: {err}"));
+                    self.controller.render_failed();
+                } else {
+                    // the worker has been restarted, try once more
+                    self.render_retried = true;
+                    self.controller.has_changed = true;
+                }
+            }
+        }
+        if let Some(remaining) = self.autosave {
+            let remaining = remaining - timing.fixed_time_step;
+            if remaining <= 0.0 {
+                self.save_prefs();
+            } else {
+                self.autosave = Some(remaining);
+            }
+        }
+        for err in self.controller.audio.take_errors() {
+            self.toasts.error(format!("Audio error: {err}"));
+        }
+        self.toasts.update(timing.fixed_time_step);
+        self.controller.theme_name_time -= timing.fixed_time_step;
+        self.lockout -= timing.fixed_time_step;
+        //reset so child scenes are only opened once
+        std::mem::replace(&mut self.result, Nothing)
     }
 
-    fn resuming(&mut self, _: Option<SR>) {}
-}
-
-fn convert_to_data(sample: Sample) -> Vec<f32> {
-    let mut mixer = Mixer::new(SAMPLE_RATE as usize);
-    mixer.play(sample);
-    let mut output = vec![];
-    let mut buffer = [0.0; 100];
-    loop {
-        mixer.generate(&mut buffer);
-        if buffer.iter().any(|&num| num != 0.0 && num != -0.0) {
-            output.extend_from_slice(
-                &buffer
-                    .iter()
-                    .copied()
-                    .filter(|v| v.is_normal())
-                    .collect::<Vec<f32>>(),
-            );
-        } else {
-            break;
+    fn resuming(&mut self, result: Option<SR>) {
+        //the key may have been released while the child scene was open
+        self.repeat.clear();
+        match result {
+            Some(SR::Theme(theme)) => self.save_theme(theme),
+            Some(SR::Keys(keys)) => {
+                self.prefs.data.keys = keys.overrides();
+                self.controller.set_keys(keys, &self.themes[self.theme]);
+                self.settings_changed();
+            }
+            Some(SR::Name(edit)) => self.rename(edit),
+            None => {}
         }
     }
-    output
 }
 
-fn func_key_idx(key: KeyCode) -> usize {
-    match key {
-        KeyCode::Digit1 => 0,
-        KeyCode::Digit2 => 1,
-        KeyCode::Digit3 => 2,
-        KeyCode::Digit4 => 3,
-        KeyCode::Digit5 => 4,
-        KeyCode::Digit6 => 5,
-        KeyCode::Digit7 => 6,
-        KeyCode::Digit8 => 7,
-        KeyCode::Digit9 => 8,
-        KeyCode::Digit0 => 9,
-        _ => panic!("Invalid key code {key:?}"),
-    }
+/// Slot number shown to the user, matching the key
+fn slot_number(idx: usize) -> usize {
+    (idx + 1) % 10
+}
+
+/// Whether shift, control and alt are held
+fn modifiers(held_keys: &FxHashSet<KeyCode>) -> (bool, bool, bool) {
+    (
+        held_keys.contains(&KeyCode::ShiftLeft) || held_keys.contains(&KeyCode::ShiftRight),
+        held_keys.contains(&KeyCode::ControlLeft) || held_keys.contains(&KeyCode::ControlRight),
+        held_keys.contains(&KeyCode::AltLeft) || held_keys.contains(&KeyCode::AltRight),
+    )
 }
 
 fn is_modifier(key: KeyCode) -> bool {
@@ -186,3 +743,28 @@ fn is_modifier(key: KeyCode) -> bool {
             | KeyCode::AltRight
     )
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn confirms_with_same_keys_only() {
+        let keys = KeyMap::default();
+        let slot = keys.key(Action::SaveSlot(2)).unwrap();
+        let other = keys.key(Action::SaveSlot(3)).unwrap();
+        let overwrite = Confirm::Overwrite(2);
+        assert!(overwrite.is_confirmed_by(&keys, slot, false, false, false));
+        assert!(overwrite.is_confirmed_by(&keys, KeyCode::KeyY, true, false, false));
+        // shift loads the slot, it must not save over it
+        assert!(!overwrite.is_confirmed_by(&keys, slot, true, false, false));
+        assert!(!overwrite.is_confirmed_by(&keys, slot, false, true, false));
+        assert!(!overwrite.is_confirmed_by(&keys, slot, false, false, true));
+        assert!(!overwrite.is_confirmed_by(&keys, other, false, false, false));
+        let delete = Confirm::Delete(2);
+        assert!(delete.is_confirmed_by(&keys, slot, false, true, false));
+        assert!(!delete.is_confirmed_by(&keys, slot, false, false, false));
+        assert!(!delete.is_confirmed_by(&keys, slot, true, true, false));
+        assert!(!delete.is_confirmed_by(&keys, slot, false, true, true));
+    }
+}
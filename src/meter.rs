@@ -0,0 +1,282 @@
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+pub const DEFAULT_PEAK_TARGET: f32 = -1.0;
+pub const DEFAULT_LOUDNESS_TARGET: f32 = -16.0;
+
+/// Length of the blocks loudness is measured over, in seconds
+const BLOCK_SECS: f32 = 0.4;
+/// Blocks overlap by 75%
+const BLOCK_STEP: f32 = BLOCK_SECS / 4.0;
+const ABSOLUTE_GATE: f32 = -70.0;
+const RELATIVE_GATE: f32 = -10.0;
+
+/// Level the normalise action adjusts the volume to reach, in dBFS or LUFS
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum NormaliseTarget {
+    Peak(f32),
+    Loudness(f32),
+}
+
+impl Default for NormaliseTarget {
+    fn default() -> Self {
+        NormaliseTarget::Peak(DEFAULT_PEAK_TARGET)
+    }
+}
+
+impl NormaliseTarget {
+    /// Switch between peak and loudness, using the default level
+    pub fn toggle(&self) -> NormaliseTarget {
+        match self {
+            NormaliseTarget::Peak(_) => NormaliseTarget::Loudness(DEFAULT_LOUDNESS_TARGET),
+            NormaliseTarget::Loudness(_) => NormaliseTarget::Peak(DEFAULT_PEAK_TARGET),
+        }
+    }
+
+    /// How far `levels` is from the target, in dB
+    pub fn difference(&self, levels: &Levels) -> f32 {
+        match self {
+            NormaliseTarget::Peak(target) => target - levels.peak,
+            NormaliseTarget::Loudness(target) => target - levels.loudness,
+        }
+    }
+}
+
+/// Levels of a rendered sound
+///
+/// `peak` and `rms` are in dBFS, `loudness` is an estimate of the integrated loudness in LUFS
+/// following ITU-R BS.1770 (K-weighted and gated), sounds shorter than a block are measured as
+/// one block
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Levels {
+    pub peak: f32,
+    pub rms: f32,
+    pub loudness: f32,
+}
+
+impl Default for Levels {
+    fn default() -> Self {
+        Levels {
+            peak: f32::NEG_INFINITY,
+            rms: f32::NEG_INFINITY,
+            loudness: f32::NEG_INFINITY,
+        }
+    }
+}
+
+impl Levels {
+    pub fn measure(data: &[f32], sample_rate: u32) -> Self {
+        if data.is_empty() {
+            return Levels::default();
+        }
+        let peak = data
+            .iter()
+            .fold(0.0_f32, |peak, value| peak.max(value.abs()));
+        let mean_square = data.iter().map(|value| value * value).sum::<f32>() / data.len() as f32;
+        Levels {
+            peak: to_db(peak),
+            rms: 10.0 * mean_square.log10(),
+            loudness: loudness(data, sample_rate),
+        }
+    }
+}
+
+fn to_db(value: f32) -> f32 {
+    20.0 * value.log10()
+}
+
+/// Format a level to one decimal place, or `-INF` for silence
+pub fn format_db(value: f32) -> String {
+    if value.is_finite() {
+        format!("{value:.1}")
+    } else {
+        String::from("-INF")
+    }
+}
+
+fn loudness(data: &[f32], sample_rate: u32) -> f32 {
+    let rate = sample_rate as f32;
+    let mut shelf = Biquad::high_shelf(rate);
+    let mut high_pass = Biquad::high_pass(rate);
+    let weighted: Vec<f32> = data
+        .iter()
+        .map(|value| high_pass.process(shelf.process(*value)))
+        .collect();
+    let block = ((BLOCK_SECS * rate) as usize).min(weighted.len());
+    let step = ((BLOCK_STEP * rate) as usize).max(1);
+    let blocks: Vec<f32> = (0..=weighted.len() - block)
+        .step_by(step)
+        .map(|start| {
+            weighted[start..start + block]
+                .iter()
+                .map(|value| value * value)
+                .sum::<f32>()
+                / block as f32
+        })
+        .collect();
+    let block_loudness = |power: f32| -0.691 + 10.0 * power.log10();
+    let gated_mean = |gate: f32| {
+        let gated: Vec<f32> = blocks
+            .iter()
+            .copied()
+            .filter(|power| block_loudness(*power) > gate)
+            .collect();
+        if gated.is_empty() {
+            None
+        } else {
+            Some(gated.iter().sum::<f32>() / gated.len() as f32)
+        }
+    };
+    match gated_mean(ABSOLUTE_GATE) {
+        None => f32::NEG_INFINITY,
+        Some(power) => {
+            let relative = block_loudness(power) + RELATIVE_GATE;
+            gated_mean(relative)
+                .map(block_loudness)
+                .unwrap_or(f32::NEG_INFINITY)
+        }
+    }
+}
+
+/// Second order IIR filter used for K-weighting
+struct Biquad {
+    b: [f32; 3],
+    a: [f32; 2],
+    x: [f32; 2],
+    y: [f32; 2],
+}
+
+impl Biquad {
+    fn new(b: [f32; 3], a: [f32; 3]) -> Self {
+        Biquad {
+            b: [b[0] / a[0], b[1] / a[0], b[2] / a[0]],
+            a: [a[1] / a[0], a[2] / a[0]],
+            x: [0.0; 2],
+            y: [0.0; 2],
+        }
+    }
+
+    /// Pre-filter that models the acoustic effect of the head
+    fn high_shelf(rate: f32) -> Self {
+        let freq = 1_681.974_5;
+        let gain = 3.999_844;
+        let q = 0.707_175_2;
+        let k = (PI * freq / rate).tan();
+        let vh = 10_f32.powf(gain / 20.0);
+        let vb = vh.powf(0.499_666_77);
+        Biquad::new(
+            [
+                vh + vb * k / q + k * k,
+                2.0 * (k * k - vh),
+                vh - vb * k / q + k * k,
+            ],
+            [
+                1.0 + k / q + k * k,
+                2.0 * (k * k - 1.0),
+                1.0 - k / q + k * k,
+            ],
+        )
+    }
+
+    /// RLB weighting curve
+    fn high_pass(rate: f32) -> Self {
+        let freq = 38.135_47;
+        let q = 0.500_327;
+        let k = (PI * freq / rate).tan();
+        Biquad::new(
+            [1.0, -2.0, 1.0],
+            [
+                1.0 + k / q + k * k,
+                2.0 * (k * k - 1.0),
+                1.0 - k / q + k * k,
+            ],
+        )
+    }
+
+    fn process(&mut self, value: f32) -> f32 {
+        let output = self.b[0] * value + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+        self.x = [value, self.x[0]];
+        self.y = [output, self.y[0]];
+        output
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::audio::SAMPLE_RATE;
+    use crate::render::DEFAULT_MAX_LENGTH;
+    use crate::settings::SoundSave;
+
+    fn sine(amplitude: f32, freq: f32, sample_rate: u32) -> Vec<f32> {
+        (0..sample_rate)
+            .map(|i| amplitude * (2.0 * PI * freq * i as f32 / sample_rate as f32).sin())
+            .collect()
+    }
+
+    fn assert_near(value: f32, expected: f32) {
+        assert!((value - expected).abs() < 0.05, "{value} != {expected}");
+    }
+
+    #[test]
+    fn full_scale_sine() {
+        let levels = Levels::measure(&sine(1.0, 1000.0, 48_000), 48_000);
+        assert_near(levels.peak, 0.0);
+        assert_near(levels.rms, -3.01);
+        // K-weighting is calibrated so a 1khz sine reads the same as its RMS
+        assert_near(levels.loudness, -3.01);
+    }
+
+    #[test]
+    fn half_scale_sine() {
+        let levels = Levels::measure(&sine(0.5, 1000.0, 44_100), 44_100);
+        assert_near(levels.peak, -6.02);
+        assert_near(levels.rms, -9.03);
+        assert_near(levels.loudness, -9.03);
+    }
+
+    #[test]
+    fn silence() {
+        for data in [vec![], vec![0.0; 48_000]] {
+            let levels = Levels::measure(&data, 48_000);
+            assert_eq!(levels, Levels::default());
+            assert_eq!(format_db(levels.peak), "-INF");
+            assert_eq!(format_db(levels.loudness), "-INF");
+        }
+        assert_eq!(format_db(-3.04), "-3.0");
+    }
+
+    #[test]
+    fn gates_quiet_blocks() {
+        let mut data = sine(1.0, 1000.0, 48_000);
+        data.extend(vec![0.0; 48_000 * 4]);
+        let levels = Levels::measure(&data, 48_000);
+        assert_near(levels.rms, -10.0);
+        // only the blocks that overlap the end of the sine lower it
+        assert!(levels.loudness > -4.0 && levels.loudness < -3.01);
+    }
+
+    #[test]
+    fn normalise_reaches_target() {
+        let mut sound = SoundSave::new_blank();
+        sound.volume = 0.3;
+        sound.attack = 0.05;
+        sound.decay = 0.1;
+        sound.sustain = 0.6;
+        sound.release = 0.5;
+        sound.freq = 440;
+        for target in [
+            NormaliseTarget::Peak(-1.0),
+            NormaliseTarget::Loudness(-16.0),
+        ] {
+            sound.normalise(target, DEFAULT_MAX_LENGTH);
+            let levels = Levels::measure(&sound.create_data(DEFAULT_MAX_LENGTH), SAMPLE_RATE);
+            assert!(
+                target.difference(&levels).abs() < 0.05,
+                "{target:?} {levels:?}"
+            );
+        }
+    }
+}
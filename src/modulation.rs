@@ -0,0 +1,122 @@
+use std::f32::consts::TAU;
+
+/// Low frequency oscillator settings used by vibrato and tremolo
+///
+/// `rate` is in hertz, `delay` is in seconds
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Lfo {
+    pub rate: f32,
+    pub depth: f32,
+    pub delay: f32,
+}
+
+impl Lfo {
+    /// Phase of the LFO at `t` seconds, or None if it hasn't started yet
+    fn phase(&self, t: f32) -> Option<f32> {
+        if t < self.delay {
+            None
+        } else {
+            Some((t - self.delay) * self.rate * TAU)
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Modulation {
+    pub vibrato: Option<Lfo>,
+    pub tremolo: Option<Lfo>,
+}
+
+impl Modulation {
+    pub fn apply(&self, data: Vec<f32>, sample_rate: usize) -> Vec<f32> {
+        let mut data = match &self.vibrato {
+            Some(lfo) => vibrato(&data, lfo, sample_rate),
+            None => data,
+        };
+        if let Some(lfo) = &self.tremolo {
+            tremolo(&mut data, lfo, sample_rate);
+        }
+        data
+    }
+}
+
+/// Resample `data` with a playback speed that follows the LFO, so the pitch wobbles
+///
+/// Depth of 1.0 varies the pitch by ±50%, higher depths are treated as 1.0
+fn vibrato(data: &[f32], lfo: &Lfo, sample_rate: usize) -> Vec<f32> {
+    if data.len() < 2 {
+        return data.to_vec();
+    }
+    let last = (data.len() - 1) as f32;
+    let mut output = Vec::with_capacity(data.len());
+    let mut pos = 0.0;
+    while pos < last {
+        let idx = pos as usize;
+        let frac = pos - idx as f32;
+        output.push(data[idx] + (data[idx + 1] - data[idx]) * frac);
+        let t = output.len() as f32 / sample_rate as f32;
+        pos += match lfo.phase(t) {
+            Some(phase) => 1.0 + lfo.depth.clamp(0.0, 1.0) * 0.5 * phase.sin(),
+            None => 1.0,
+        };
+    }
+    output
+}
+
+/// Scale the volume of `data` by the LFO
+///
+/// Depth of 1.0 makes the volume drop to silence at the bottom of each cycle
+fn tremolo(data: &mut [f32], lfo: &Lfo, sample_rate: usize) {
+    for (i, value) in data.iter_mut().enumerate() {
+        if let Some(phase) = lfo.phase(i as f32 / sample_rate as f32) {
+            *value *= 1.0 - lfo.depth.clamp(0.0, 1.0) * (1.0 - phase.cos()) * 0.5;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ramp(len: usize) -> Vec<f32> {
+        (0..len).map(|i| i as f32 / len as f32).collect()
+    }
+
+    #[test]
+    fn vibrato_terminates_at_full_depth() {
+        let data = ramp(5000);
+        for depth in [1.0, 2.0, 100.0] {
+            let lfo = Lfo {
+                rate: 50.0,
+                depth,
+                delay: 0.0,
+            };
+            let output = vibrato(&data, &lfo, 1000);
+            assert!(output.len() > data.len() / 2 && output.len() < data.len() * 2);
+        }
+    }
+
+    #[test]
+    fn unchanged_before_delay() {
+        let data = ramp(1000);
+        let lfo = Lfo {
+            rate: 20.0,
+            depth: 0.8,
+            delay: 0.1,
+        };
+        let modulation = Modulation {
+            vibrato: Some(lfo),
+            tremolo: None,
+        };
+        let output = modulation.apply(data.clone(), 1000);
+        assert_eq!(output[..100], data[..100]);
+        assert_ne!(output[100..200], data[100..200]);
+        let modulation = Modulation {
+            vibrato: None,
+            tremolo: Some(lfo),
+        };
+        let output = modulation.apply(data.clone(), 1000);
+        assert_eq!(output[..100], data[..100]);
+        assert_ne!(output[100..200], data[100..200]);
+    }
+}
@@ -0,0 +1,181 @@
+use crate::theme::Theme;
+use crate::{SN, SR};
+use pixels_graphics_lib::buffer_graphics_lib::prelude::TextPos::Px;
+use pixels_graphics_lib::buffer_graphics_lib::prelude::*;
+use pixels_graphics_lib::prelude::PixelFont::Standard4x5;
+use pixels_graphics_lib::prelude::SceneUpdateResult::{Nothing, Pop};
+use pixels_graphics_lib::prelude::*;
+use pixels_graphics_lib::utilities::key_press_to_char;
+
+const MAX_NAME: usize = 32;
+const MAX_NOTES: usize = 240;
+/// Characters per line of notes
+const NOTES_WIDTH: usize = 56;
+const LEFT: isize = 20;
+const TOP: isize = 110;
+
+/// Name and notes for the current sound (`slot` is None) or a saved sound
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameEdit {
+    pub slot: Option<usize>,
+    pub name: String,
+    pub notes: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameEditorSetup {
+    pub edit: NameEdit,
+    /// Shown as the name if it's left empty
+    pub summary: String,
+    pub theme: Theme,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Field {
+    Name,
+    Notes,
+}
+
+/// Text entry for a sound's name and notes, Enter returns them to the main scene
+pub struct NameEditorScene {
+    edit: NameEdit,
+    summary: String,
+    theme: Theme,
+    field: Field,
+    result: SceneUpdateResult<SR, SN>,
+}
+
+impl NameEditorScene {
+    pub fn new(setup: NameEditorSetup) -> Self {
+        NameEditorScene {
+            edit: setup.edit,
+            summary: setup.summary,
+            theme: setup.theme,
+            field: Field::Name,
+            result: Nothing,
+        }
+    }
+}
+
+/// Split `text` into lines of at most `width` characters
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.is_empty() {
+        return vec![String::new()];
+    }
+    chars
+        .chunks(width)
+        .map(|line| line.iter().collect())
+        .collect()
+}
+
+impl Scene<SR, SN> for NameEditorScene {
+    fn render(&self, graphics: &mut Graphics, _: &MouseData, _: &FxHashSet<KeyCode>) {
+        let theme = &self.theme;
+        let rect = Rect::new((LEFT - 8, TOP - 8), (340 - LEFT + 8, TOP + 112));
+        graphics.draw_rect(rect.clone(), fill(theme.background));
+        graphics.draw_rect(rect, stroke(theme.active));
+        let title = match self.edit.slot {
+            Some(slot) => format!("SLOT {}", (slot + 1) % 10),
+            None => String::from("CURRENT SOUND"),
+        };
+        graphics.draw_text(&title, Px(LEFT, TOP), (theme.active, Standard4x5));
+        graphics.draw_text(
+            &self.summary,
+            Px(LEFT, TOP + 8),
+            (theme.inactive, Standard4x5),
+        );
+
+        let color = |field: Field| {
+            if self.field == field {
+                theme.active
+            } else {
+                theme.inactive
+            }
+        };
+        let cursor = |field: Field| if self.field == field { "_" } else { "" };
+        graphics.draw_text(
+            "NAME",
+            Px(LEFT, TOP + 22),
+            (color(Field::Name), Standard4x5),
+        );
+        let name = if self.edit.name.is_empty() && self.field != Field::Name {
+            self.summary.clone()
+        } else {
+            format!("{}{}", self.edit.name.to_uppercase(), cursor(Field::Name))
+        };
+        graphics.draw_text(
+            &name,
+            Px(LEFT + 8, TOP + 30),
+            (color(Field::Name), Standard4x5),
+        );
+
+        graphics.draw_text(
+            "NOTES",
+            Px(LEFT, TOP + 44),
+            (color(Field::Notes), Standard4x5),
+        );
+        let notes = format!("{}{}", self.edit.notes.to_uppercase(), cursor(Field::Notes));
+        for (i, line) in wrap(&notes, NOTES_WIDTH).iter().enumerate() {
+            graphics.draw_text(
+                line,
+                Px(LEFT + 8, TOP + 52 + i as isize * 7),
+                (color(Field::Notes), Standard4x5),
+            );
+        }
+
+        graphics.draw_text(
+            "[TAB] NAME/NOTES  [ENTER] DONE  [ESC] CANCEL",
+            Px(LEFT, TOP + 98),
+            (theme.inactive, Standard4x5),
+        );
+    }
+
+    fn on_key_down(&mut self, key: KeyCode, _: &MouseData, held_keys: &FxHashSet<KeyCode>) {
+        let (text, max) = match self.field {
+            Field::Name => (&mut self.edit.name, MAX_NAME),
+            Field::Notes => (&mut self.edit.notes, MAX_NOTES),
+        };
+        match key {
+            KeyCode::Tab | KeyCode::ArrowUp | KeyCode::ArrowDown => {
+                self.field = match self.field {
+                    Field::Name => Field::Notes,
+                    Field::Notes => Field::Name,
+                }
+            }
+            KeyCode::Backspace => {
+                text.pop();
+            }
+            KeyCode::Enter | KeyCode::NumpadEnter => {
+                let mut edit = self.edit.clone();
+                edit.name = edit.name.trim().to_string();
+                edit.notes = edit.notes.trim().to_string();
+                self.result = Pop(Some(SR::Name(edit)));
+            }
+            KeyCode::Escape => self.result = Pop(None),
+            _ => {
+                let shift = held_keys.contains(&KeyCode::ShiftLeft)
+                    || held_keys.contains(&KeyCode::ShiftRight);
+                if let Some(chr) = key_press_to_char(key, shift) {
+                    if text.chars().count() < max {
+                        text.push(chr);
+                    }
+                }
+            }
+        }
+    }
+
+    fn update(
+        &mut self,
+        _: &Timing,
+        _: &MouseData,
+        _: &FxHashSet<KeyCode>,
+        _: &Window,
+    ) -> SceneUpdateResult<SR, SN> {
+        self.result.clone()
+    }
+
+    fn is_dialog(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,180 @@
+/// MIDI note number of A4
+const A4: i32 = 69;
+pub const A4_FREQ: f32 = 440.0;
+
+const MIN_FREQ: f32 = 1.0;
+const MAX_FREQ: f32 = 20_000.0;
+
+pub const NOTE_NAMES: &[&str] = &[
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+pub const SCALE_NAMES: &[&str] = &["Chrom", "Major", "Minor", "MajPen", "MinPen", "Blues"];
+
+/// Semitones above the root of each note in the scale, in the same order as [SCALE_NAMES]
+const SCALE_INTERVALS: [&[i32]; 6] = [
+    &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+    &[0, 2, 4, 5, 7, 9, 11],
+    &[0, 2, 3, 5, 7, 8, 10],
+    &[0, 2, 4, 7, 9],
+    &[0, 3, 5, 7, 10],
+    &[0, 3, 5, 6, 7, 10],
+];
+
+/// Reference pitch and optional scale for converting between notes and frequencies
+///
+/// `scale` is an index into [SCALE_NAMES] and `root` is the semitone above C the scale starts on
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Tuning {
+    pub a4: f32,
+    pub scale: Option<usize>,
+    pub root: i32,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Tuning {
+            a4: A4_FREQ,
+            scale: None,
+            root: 0,
+        }
+    }
+}
+
+impl Tuning {
+    /// Equal tempered frequency of a MIDI note number
+    pub fn note_freq(&self, note: i32) -> f32 {
+        self.a4 * 2_f32.powf((note - A4) as f32 / 12.0)
+    }
+
+    /// Fractional MIDI note number of a frequency
+    fn freq_note(&self, freq: f32) -> f32 {
+        A4 as f32 + 12.0 * (freq.max(MIN_FREQ) / self.a4).log2()
+    }
+
+    /// Nearest MIDI note number of a frequency
+    pub fn midi_note(&self, freq: f32) -> i32 {
+        self.freq_note(freq).round() as i32
+    }
+
+    /// Nearest note name and how far off it is, such as `A4 +3c`
+    pub fn describe(&self, freq: f32) -> String {
+        let note = self.freq_note(freq);
+        let nearest = note.round() as i32;
+        let cents = ((note - nearest as f32) * 100.0).round() as i32;
+        format!(
+            "{}{} {:+}c",
+            NOTE_NAMES[nearest.rem_euclid(12) as usize],
+            nearest.div_euclid(12) - 1,
+            cents
+        )
+    }
+
+    fn in_scale(&self, note: i32) -> bool {
+        match self.scale {
+            None => true,
+            Some(scale) => SCALE_INTERVALS[scale].contains(&(note - self.root).rem_euclid(12)),
+        }
+    }
+
+    /// Note in the scale closest to `freq`
+    fn nearest_note(&self, freq: f32) -> i32 {
+        let note = self.freq_note(freq).round() as i32;
+        (0..12)
+            .flat_map(|dist| [note - dist, note + dist])
+            .find(|note| self.in_scale(*note))
+            .unwrap_or(note)
+    }
+
+    /// Frequency of the note in the scale closest to `freq`
+    pub fn snap(&self, freq: f32) -> f32 {
+        self.note_freq(self.nearest_note(freq))
+            .clamp(MIN_FREQ, MAX_FREQ)
+    }
+
+    /// Frequency of the note `steps` notes of the scale away from `freq`, or `steps` octaves if
+    /// `octaves` is true
+    pub fn step(&self, freq: f32, steps: i32, octaves: bool) -> f32 {
+        let mut note = self.nearest_note(freq);
+        if octaves {
+            note += steps * 12;
+        } else {
+            for _ in 0..steps.abs() {
+                note += steps.signum();
+                while !self.in_scale(note) {
+                    note += steps.signum();
+                }
+            }
+        }
+        self.note_freq(note).clamp(MIN_FREQ, MAX_FREQ)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_freq(value: f32, expected: f32) {
+        assert!((value - expected).abs() < 0.01, "{value} != {expected}");
+    }
+
+    #[test]
+    fn describes_notes() {
+        let tuning = Tuning::default();
+        assert_eq!(tuning.describe(440.0), "A4 +0c");
+        assert_eq!(tuning.describe(445.0), "A4 +20c");
+        assert_eq!(tuning.describe(435.0), "A4 -20c");
+        assert_eq!(tuning.describe(261.63), "C4 +0c");
+        assert_eq!(tuning.describe(27.5), "A0 +0c");
+        let tuning = Tuning {
+            a4: 432.0,
+            ..Tuning::default()
+        };
+        assert_eq!(tuning.describe(440.0), "A4 +32c");
+    }
+
+    #[test]
+    fn snaps_to_scale() {
+        let chromatic = Tuning::default();
+        assert_freq(chromatic.snap(445.0), 440.0);
+        assert_freq(chromatic.snap(270.0), 277.18);
+        let c_major = Tuning {
+            scale: Some(1),
+            ..Tuning::default()
+        };
+        // C# is as close to C as D, the lower note wins
+        assert_freq(c_major.snap(277.18), 261.63);
+        assert_freq(c_major.snap(445.0), 440.0);
+        let d_major = Tuning {
+            scale: Some(1),
+            root: 2,
+            ..Tuning::default()
+        };
+        assert_freq(d_major.snap(349.23), 329.63);
+        assert_freq(d_major.snap(360.0), 369.99);
+        assert_eq!(d_major.snap(25_000.0), MAX_FREQ);
+    }
+
+    #[test]
+    fn steps_through_scale() {
+        let c_major = Tuning {
+            scale: Some(1),
+            ..Tuning::default()
+        };
+        assert_freq(c_major.step(261.63, 1, false), 293.66);
+        assert_freq(c_major.step(329.63, 1, false), 349.23);
+        assert_freq(c_major.step(261.63, -1, false), 246.94);
+        assert_freq(c_major.step(261.63, 7, false), 523.25);
+        assert_freq(c_major.step(440.0, 1, true), 880.0);
+        assert_freq(c_major.step(440.0, -2, true), 110.0);
+        let a_minor_pentatonic = Tuning {
+            scale: Some(4),
+            root: 9,
+            ..Tuning::default()
+        };
+        assert_freq(a_minor_pentatonic.step(440.0, 1, false), 523.25);
+        assert_freq(a_minor_pentatonic.step(440.0, -1, false), 392.0);
+        assert_eq!(c_major.step(19_000.0, 2, true), MAX_FREQ);
+        assert_eq!(c_major.step(2.0, -2, true), MIN_FREQ);
+    }
+}
@@ -0,0 +1,115 @@
+use crate::settings::Settings;
+use std::fs;
+use std::panic;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+
+const FILE_NAME: &str = "recovery.json";
+
+static FILE: OnceLock<PathBuf> = OnceLock::new();
+/// Latest settings (including the current sound) to write if the app crashes
+static SNAPSHOT: Mutex<Option<Settings>> = Mutex::new(None);
+
+/// Install a panic hook that writes the latest snapshot to `dir` before the app exits
+///
+/// Only panics on the main thread are written, others (such as the waveform worker) are recovered
+/// from without the app exiting
+pub fn install(dir: PathBuf, settings: &Settings) {
+    let _ = FILE.set(dir.join(FILE_NAME));
+    update(settings);
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if thread::current().name() == Some("main") {
+            write_snapshot();
+        }
+        default_hook(info);
+    }));
+}
+
+pub fn update(settings: &Settings) {
+    let mut snapshot = SNAPSHOT.lock().unwrap_or_else(|err| err.into_inner());
+    *snapshot = Some(settings.clone());
+}
+
+/// Called from the panic hook so must not panic itself
+fn write_snapshot() {
+    let Some(path) = FILE.get() else {
+        return;
+    };
+    // the panic may have happened while the snapshot was being updated
+    let Ok(snapshot) = SNAPSHOT.try_lock() else {
+        return;
+    };
+    let Some(settings) = snapshot.as_ref() else {
+        return;
+    };
+    match serde_json::to_string(settings) {
+        Ok(json) => {
+            if let Err(err) = fs::write(path, json) {
+                eprintln!("unable to write recovery file: {err:?}");
+            } else {
+                eprintln!("unsaved work written to {}", path.display());
+            }
+        }
+        Err(err) => eprintln!("unable to create recovery file: {err:?}"),
+    }
+}
+
+/// Settings left behind by a crash, if they're different from `saved`
+pub fn load(saved: &Settings) -> Option<Settings> {
+    let path = FILE.get()?;
+    let json = fs::read_to_string(path).ok()?;
+    let recovered: Settings = match serde_json::from_str(&json) {
+        Ok(settings) => settings,
+        Err(err) => {
+            eprintln!("unable to read recovery file: {err:?}");
+            discard();
+            return None;
+        }
+    };
+    if comparable(&recovered) == comparable(saved) {
+        discard();
+        return None;
+    }
+    Some(recovered)
+}
+
+/// `settings` as JSON without the current sound's timestamp, which changes every time it's saved
+fn comparable(settings: &Settings) -> Option<serde_json::Value> {
+    let mut settings = settings.clone();
+    if let Some(current) = &mut settings.current {
+        current.when = Default::default();
+    }
+    serde_json::to_value(settings).ok()
+}
+
+pub fn discard() {
+    if let Some(path) = FILE.get() {
+        if path.exists() {
+            if let Err(err) = fs::remove_file(path) {
+                eprintln!("unable to delete recovery file: {err:?}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::settings::SoundSave;
+
+    #[test]
+    fn ignores_current_timestamp() {
+        let saved = Settings {
+            current: Some(SoundSave::new_blank()),
+            ..Settings::default()
+        };
+        let mut recovered = saved.clone();
+        let current = recovered.current.as_mut().unwrap();
+        current.when += chrono::Duration::seconds(5);
+        assert_eq!(comparable(&recovered), comparable(&saved));
+        recovered.current.as_mut().unwrap().volume += 0.1;
+        assert_ne!(comparable(&recovered), comparable(&saved));
+    }
+}
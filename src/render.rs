@@ -0,0 +1,280 @@
+use usfx::{Mixer, Sample};
+
+pub const DEFAULT_MAX_LENGTH: f32 = 30.0;
+/// Samples generated per call to the mixer, must be less than the sample rate
+const BLOCK_LEN: usize = 1024;
+
+/// Number of samples in `max_length` seconds, the limit on how long rendered sounds can be so
+/// extreme envelopes can't take forever
+pub fn max_samples(max_length: f32, sample_rate: usize) -> usize {
+    (max_length.max(0.0) * sample_rate as f32) as usize
+}
+
+/// ADSR envelope settings, these must match those set on the [Sample] as usfx doesn't expose them
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Envelope {
+    pub attack: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub release: f32,
+}
+
+impl Envelope {
+    /// Number of samples until the envelope finishes, or `max_samples` if it's sooner
+    ///
+    /// This follows the same steps (and floating point maths) as the usfx envelope
+    pub fn length(&self, sample_rate: usize, max_samples: usize) -> usize {
+        let rate = sample_rate as f32;
+        let attack_slope = 1.0 / self.attack / rate;
+        let decay_slope = 1.0 / self.decay / self.sustain / rate;
+        let release_slope = 1.0 / self.release / self.sustain / rate;
+        let mut decay_start = None;
+        let mut release_start = None;
+        for i in 0..max_samples {
+            match (decay_start, release_start) {
+                (None, _) => {
+                    if i as f32 * attack_slope >= 1.0 {
+                        decay_start = Some(i);
+                    }
+                }
+                (Some(start), None) => {
+                    if 1.0 - ((i - start) as f32 * decay_slope) <= self.sustain {
+                        release_start = Some(i);
+                    }
+                }
+                (_, Some(start)) => {
+                    if self.sustain - ((i - start) as f32 * release_slope) <= 0.0 {
+                        return i;
+                    }
+                }
+            }
+        }
+        max_samples
+    }
+
+    /// Copy of the envelope with each stage shortened so it finishes within `samples`
+    ///
+    /// The sustain level is unchanged, it's returned as is if it's already short enough
+    pub fn fit(&self, samples: usize, sample_rate: usize) -> Envelope {
+        let mut scale = 1.0;
+        loop {
+            let envelope = Envelope {
+                attack: self.attack * scale,
+                decay: self.decay * scale,
+                sustain: self.sustain,
+                release: self.release * scale,
+            };
+            let length = envelope.length(sample_rate, samples);
+            // the envelope finishes on the sample after its length
+            if length < samples || scale <= f32::EPSILON {
+                return envelope;
+            }
+            scale *= (samples as f32 / (length + 1) as f32).min(0.95);
+        }
+    }
+
+    /// Set the envelope on `sample`
+    pub fn apply(&self, sample: &mut Sample) {
+        sample
+            .env_attack(self.attack)
+            .env_decay(self.decay)
+            .env_sustain(self.sustain)
+            .env_release(self.release);
+    }
+}
+
+/// Checked between blocks while rendering, once it returns true rendering stops and gives None
+pub type Cancelled<'a> = &'a dyn Fn() -> bool;
+
+/// Render `sample` until `envelope` finishes (or after `max_samples`)
+///
+/// Every sample is kept, including silence, other than NaN or infinite values which usfx creates
+/// at the start of an envelope stage with a length of 0, these are replaced with 0.0
+pub fn render(
+    sample: Sample,
+    envelope: &Envelope,
+    sample_rate: usize,
+    max_samples: usize,
+    cancelled: Cancelled,
+) -> Option<Vec<f32>> {
+    let length = envelope.length(sample_rate, max_samples);
+    render_at(&[(0, sample)], length, sample_rate, cancelled)
+}
+
+/// Render `length` samples of one mixer, playing each sample once the output reaches its offset
+///
+/// usfx applies each sample's envelope to the whole mixer output, so a sample must finish before
+/// the next one starts, see [Envelope::fit]
+pub fn render_at(
+    samples: &[(usize, Sample)],
+    length: usize,
+    sample_rate: usize,
+    cancelled: Cancelled,
+) -> Option<Vec<f32>> {
+    let mut mixer = Mixer::new(sample_rate);
+    let mut output = vec![0.0; length];
+    let mut pos = 0;
+    for (at, sample) in samples {
+        let at = (*at).clamp(pos, length);
+        generate(&mut mixer, &mut output[pos..at], cancelled)?;
+        pos = at;
+        mixer.play(*sample);
+    }
+    generate(&mut mixer, &mut output[pos..], cancelled)?;
+    for value in output.iter_mut() {
+        if !value.is_finite() {
+            *value = 0.0;
+        }
+    }
+    Some(output)
+}
+
+fn generate(mixer: &mut Mixer, output: &mut [f32], cancelled: Cancelled) -> Option<()> {
+    for block in output.chunks_mut(BLOCK_LEN) {
+        if cancelled() {
+            return None;
+        }
+        mixer.generate(block);
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use usfx::OscillatorType;
+
+    const MAX: usize = 30_000;
+
+    fn sample(envelope: &Envelope, osc: OscillatorType) -> Sample {
+        let mut sample = Sample::default();
+        sample
+            .osc_type(osc)
+            .osc_frequency(100)
+            .volume(1.0)
+            .env_attack(envelope.attack)
+            .env_decay(envelope.decay)
+            .env_sustain(envelope.sustain)
+            .env_release(envelope.release);
+        sample
+    }
+
+    /// Render with the mixer until it's silent for a whole second, which only happens after the
+    /// envelope has finished
+    fn render_until_silent(sample: Sample) -> Vec<f32> {
+        let mut mixer = Mixer::new(1000);
+        mixer.play(sample);
+        let mut output = vec![];
+        let mut buffer = [0.0; 1000];
+        loop {
+            mixer.generate(&mut buffer);
+            if buffer.iter().all(|value| *value == 0.0) {
+                break;
+            }
+            output.extend_from_slice(&buffer);
+        }
+        while output.last() == Some(&0.0) {
+            output.pop();
+        }
+        output
+    }
+
+    #[test]
+    fn renders_whole_envelope() {
+        for envelope in [
+            Envelope {
+                attack: 0.1,
+                decay: 0.1,
+                sustain: 0.5,
+                release: 0.5,
+            },
+            Envelope {
+                attack: 0.0,
+                decay: 0.3,
+                sustain: 0.8,
+                release: 1.2,
+            },
+        ] {
+            let sample = sample(&envelope, OscillatorType::Square);
+            let expected = render_until_silent(sample);
+            let output = render(sample, &envelope, 1000, MAX, &|| false).unwrap();
+            assert_eq!(output.len(), expected.len());
+            assert!(output.iter().all(|value| value.is_finite()));
+        }
+    }
+
+    /// The saw wave crosses zero every 10 samples, the envelope starts at 0
+    #[test]
+    fn keeps_zeros() {
+        let envelope = Envelope {
+            attack: 0.2,
+            decay: 0.1,
+            sustain: 0.5,
+            release: 0.5,
+        };
+        let output = render(
+            sample(&envelope, OscillatorType::Saw),
+            &envelope,
+            1000,
+            MAX,
+            &|| false,
+        )
+        .unwrap();
+        assert_eq!(envelope.length(1000, MAX), output.len());
+        assert_eq!(output[0], 0.0);
+        assert_eq!(output[5], 0.0);
+    }
+
+    #[test]
+    fn limits_length() {
+        let envelope = Envelope {
+            attack: 0.1,
+            decay: 0.1,
+            sustain: 0.5,
+            release: 1_000_000.0,
+        };
+        let output = render(
+            sample(&envelope, OscillatorType::Square),
+            &envelope,
+            1000,
+            2500,
+            &|| false,
+        )
+        .unwrap();
+        assert_eq!(output.len(), 2500);
+    }
+
+    #[test]
+    fn stops_when_cancelled() {
+        let envelope = Envelope {
+            attack: 0.1,
+            decay: 0.1,
+            sustain: 0.5,
+            release: 1_000_000.0,
+        };
+        let blocks = std::cell::Cell::new(0);
+        let cancelled = || {
+            blocks.set(blocks.get() + 1);
+            blocks.get() > 2
+        };
+        let sample = sample(&envelope, OscillatorType::Square);
+        assert_eq!(render(sample, &envelope, 1000, MAX, &cancelled), None);
+        assert_eq!(blocks.get(), 3);
+    }
+
+    #[test]
+    fn fits_envelope() {
+        let envelope = Envelope {
+            attack: 0.1,
+            decay: 0.1,
+            sustain: 0.5,
+            release: 0.5,
+        };
+        assert_eq!(envelope.fit(1000, 1000), envelope);
+        for samples in [300, 50, 10] {
+            let fitted = envelope.fit(samples, 1000);
+            assert!(fitted.length(1000, MAX) < samples);
+            assert_eq!(fitted.sustain, envelope.sustain);
+        }
+    }
+}
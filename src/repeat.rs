@@ -0,0 +1,121 @@
+use pixels_graphics_lib::prelude::KeyCode;
+use serde::{Deserialize, Serialize};
+
+/// Seconds of repeating before the rate has doubled
+const ACCELERATION_TIME: f64 = 1.0;
+/// Fastest repeat rate, as a multiple of the normal rate
+const MAX_ACCELERATION: f64 = 4.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RepeatSettings {
+    /// Seconds a key is held before it starts repeating
+    pub delay: f64,
+    /// Repeats per second, before accelerating
+    pub rate: f64,
+}
+
+impl Default for RepeatSettings {
+    fn default() -> Self {
+        RepeatSettings {
+            delay: 0.4,
+            rate: 12.0,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct HeldKey {
+    key: KeyCode,
+    held_for: f64,
+    next_repeat: f64,
+}
+
+/// Repeats the last pressed key while it's held, getting faster the longer it's held
+#[derive(Debug)]
+pub struct KeyRepeat {
+    settings: RepeatSettings,
+    held: Option<HeldKey>,
+}
+
+impl KeyRepeat {
+    pub fn new(settings: RepeatSettings) -> Self {
+        KeyRepeat {
+            settings,
+            held: None,
+        }
+    }
+
+    /// Start repeating `key`, replacing any key already repeating
+    pub fn press(&mut self, key: KeyCode) {
+        self.held = Some(HeldKey {
+            key,
+            held_for: 0.0,
+            next_repeat: self.settings.delay,
+        });
+    }
+
+    pub fn release(&mut self, key: KeyCode) {
+        if self.held.as_ref().map(|held| held.key) == Some(key) {
+            self.held = None;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.held = None;
+    }
+
+    /// Advance by `delta` seconds, returns the held key and how many times it repeated
+    pub fn update(&mut self, delta: f64) -> Option<(KeyCode, usize)> {
+        let settings = self.settings;
+        let held = self.held.as_mut()?;
+        held.held_for += delta;
+        let mut count = 0;
+        while held.held_for >= held.next_repeat && settings.rate > 0.0 {
+            let repeating_for = held.next_repeat - settings.delay;
+            let acceleration = (1.0 + repeating_for / ACCELERATION_TIME).min(MAX_ACCELERATION);
+            held.next_repeat += 1.0 / (settings.rate * acceleration);
+            count += 1;
+        }
+        if count > 0 {
+            Some((held.key, count))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn repeats(repeat: &mut KeyRepeat, seconds: f64) -> usize {
+        let step = 1.0 / 240.0;
+        (0..(seconds / step).round() as usize)
+            .filter_map(|_| repeat.update(step))
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    #[test]
+    fn waits_for_delay() {
+        let mut repeat = KeyRepeat::new(RepeatSettings::default());
+        repeat.press(KeyCode::KeyV);
+        assert_eq!(repeats(&mut repeat, 0.39), 0);
+        assert_eq!(repeats(&mut repeat, 0.02), 1);
+        repeat.release(KeyCode::KeyV);
+        assert_eq!(repeats(&mut repeat, 1.0), 0);
+    }
+
+    #[test]
+    fn accelerates() {
+        let mut repeat = KeyRepeat::new(RepeatSettings::default());
+        repeat.press(KeyCode::KeyV);
+        repeats(&mut repeat, 0.4);
+        let first_second = repeats(&mut repeat, 1.0);
+        repeats(&mut repeat, 3.0);
+        let later_second = repeats(&mut repeat, 1.0);
+        assert!(first_second > 12);
+        assert_eq!(later_second, 48);
+    }
+}
@@ -0,0 +1,193 @@
+use crate::render::{render, render_at, Cancelled, Envelope};
+use usfx::Sample;
+
+/// Switch to `multiplier` times the frequency after `time` seconds
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Arpeggio {
+    pub multiplier: f32,
+    pub time: f32,
+}
+
+/// Timing changes within a single sound
+///
+/// `repeat` retriggers the envelope every that many seconds for the length of the sound
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Sequence {
+    pub arpeggio: Option<Arpeggio>,
+    pub repeat: Option<f32>,
+}
+
+/// One play of the envelope, from `at` until the next repeat (or the end of the sound)
+///
+/// `arp_at` is when the arpeggio frequency takes over, all in samples
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Note {
+    at: usize,
+    end: usize,
+    arp_at: Option<usize>,
+}
+
+impl Sequence {
+    pub fn is_empty(&self) -> bool {
+        self.arpeggio.is_none() && self.repeat.is_none()
+    }
+
+    /// Render `sample` (which must be set to `freq` and `envelope`) with the sequence applied,
+    /// None if it was cancelled
+    ///
+    /// Each repeat is a copy of `sample` played on one mixer at its offset, with the envelope
+    /// shortened to finish before the next repeat as usfx can't stop a sample early. The arpeggio
+    /// is rendered the same way at its frequency and switched to part way through each repeat, so
+    /// the envelope carries on across the change in pitch
+    pub fn render(
+        &self,
+        sample: Sample,
+        envelope: &Envelope,
+        freq: usize,
+        sample_rate: usize,
+        max_samples: usize,
+        cancelled: Cancelled,
+    ) -> Option<Vec<f32>> {
+        if self.is_empty() {
+            return render(sample, envelope, sample_rate, max_samples, cancelled);
+        }
+        let length = envelope.length(sample_rate, max_samples);
+        let notes = self.schedule(length, sample_rate);
+        let play = |sample: Sample| {
+            let samples: Vec<(usize, Sample)> = notes
+                .iter()
+                .map(|note| {
+                    let mut sample = sample;
+                    envelope
+                        .fit(note.end - note.at, sample_rate)
+                        .apply(&mut sample);
+                    (note.at, sample)
+                })
+                .collect();
+            render_at(&samples, length, sample_rate, cancelled)
+        };
+        let mut output = play(sample)?;
+        if let Some(arpeggio) = &self.arpeggio {
+            let mut arp_sample = sample;
+            arp_sample.osc_frequency((freq as f32 * arpeggio.multiplier).round() as usize);
+            let arp_output = play(arp_sample)?;
+            for note in &notes {
+                if let Some(arp_at) = note.arp_at {
+                    output[arp_at..note.end].copy_from_slice(&arp_output[arp_at..note.end]);
+                }
+            }
+        }
+        Some(output)
+    }
+
+    fn schedule(&self, length: usize, sample_rate: usize) -> Vec<Note> {
+        let to_samples = |secs: f32| (secs.max(0.0) * sample_rate as f32) as usize;
+        let interval = self
+            .repeat
+            .map(|secs| to_samples(secs).max(1))
+            .unwrap_or(length)
+            .max(1);
+        let arp_offset = self.arpeggio.map(|arpeggio| to_samples(arpeggio.time));
+        (0..length)
+            .step_by(interval)
+            .map(|at| {
+                let end = (at + interval).min(length);
+                Note {
+                    at,
+                    end,
+                    arp_at: arp_offset
+                        .map(|offset| at + offset)
+                        .filter(|arp_at| *arp_at < end),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn envelope() -> Envelope {
+        Envelope {
+            attack: 0.1,
+            decay: 0.2,
+            sustain: 0.5,
+            release: 1.0,
+        }
+    }
+
+    #[test]
+    fn schedules_notes() {
+        let sequence = Sequence {
+            arpeggio: Some(Arpeggio {
+                multiplier: 2.0,
+                time: 0.03,
+            }),
+            repeat: Some(0.1),
+        };
+        let notes: Vec<(usize, usize, Option<usize>)> = sequence
+            .schedule(250, 1000)
+            .iter()
+            .map(|note| (note.at, note.end, note.arp_at))
+            .collect();
+        assert_eq!(
+            notes,
+            vec![
+                (0, 100, Some(30)),
+                (100, 200, Some(130)),
+                (200, 250, Some(230))
+            ]
+        );
+    }
+
+    /// With a long release the sound stays loud as it switches to the arpeggio frequency
+    #[test]
+    fn arpeggio_keeps_envelope() {
+        let envelope = Envelope {
+            attack: 0.01,
+            decay: 0.01,
+            sustain: 0.8,
+            release: 0.5,
+        };
+        let mut sample = Sample::default();
+        sample.osc_frequency(100).volume(1.0);
+        envelope.apply(&mut sample);
+        let sequence = Sequence {
+            arpeggio: Some(Arpeggio {
+                multiplier: 2.0,
+                time: 0.1,
+            }),
+            repeat: None,
+        };
+        let output = sequence
+            .render(sample, &envelope, 100, 1000, 30_000, &|| false)
+            .unwrap();
+        assert_eq!(output.len(), envelope.length(1000, 30_000));
+        let peaks: Vec<f32> = output[50..150]
+            .chunks(10)
+            .map(|window| window.iter().fold(0.0, |peak, value| value.abs().max(peak)))
+            .collect();
+        for pair in peaks.windows(2) {
+            assert!(pair[1] > pair[0] * 0.9, "{peaks:?}");
+        }
+    }
+
+    #[test]
+    fn retriggers_envelope() {
+        let envelope = envelope();
+        let mut sample = Sample::default();
+        sample.osc_frequency(100).volume(1.0);
+        envelope.apply(&mut sample);
+        let sequence = Sequence {
+            arpeggio: None,
+            repeat: Some(0.1),
+        };
+        let output = sequence
+            .render(sample, &envelope, 100, 1000, 30_000, &|| false)
+            .unwrap();
+        assert_eq!(output.len(), envelope.length(1000, 30_000));
+        assert!(output[..100].iter().any(|value| *value != 0.0));
+        assert_eq!(output[..100], output[100..200]);
+    }
+}
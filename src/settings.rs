@@ -1,16 +1,151 @@
+use crate::audio::SAMPLE_RATE;
+use crate::export::{ExportOptions, DEFAULT_EXPORT_DIR, DEFAULT_SPRITE_GAP};
+use crate::meter::{Levels, NormaliseTarget};
+use crate::modulation::{Lfo, Modulation};
+use crate::notes::{Tuning, A4_FREQ};
+use crate::render::{max_samples, Cancelled, Envelope, DEFAULT_MAX_LENGTH};
+use crate::repeat::RepeatSettings;
+use crate::sequence::{Arpeggio, Sequence};
+use crate::theme::{themes, ThemeData};
+use crate::variation::VariationSettings;
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
-use usfx::{DutyCycle, OscillatorType};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use usfx::{DutyCycle, OscillatorType, Sample};
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
-    pub theme: usize,
+    /// Name of the theme in use, matched ignoring case as themes can be added or removed between
+    /// runs, see [crate::theme::find_theme]
+    #[serde(deserialize_with = "theme_name")]
+    pub theme: String,
     pub saved: [Option<SoundSave>; 10],
+    #[serde(default)]
+    pub export_dir: Option<PathBuf>,
+    /// Seconds of silence between sounds in audio sprites
+    #[serde(default = "default_sprite_gap")]
+    pub sprite_gap: f32,
+    #[serde(default)]
+    pub export: ExportOptions,
+    #[serde(default)]
+    pub variation: VariationSettings,
+    #[serde(default)]
+    pub normalise_target: NormaliseTarget,
+    /// Longest sound that will be rendered, in seconds
+    #[serde(default = "default_max_length")]
+    pub max_length: f32,
+    /// Sound being edited when the app was last closed
+    #[serde(default)]
+    pub current: Option<SoundSave>,
+    /// Themes made with the theme editor
+    #[serde(default)]
+    pub themes: Vec<ThemeData>,
+    /// Key bindings that differ from the defaults, action name to key name
+    #[serde(default)]
+    pub keys: BTreeMap<String, String>,
+    #[serde(default)]
+    pub repeat: RepeatSettings,
+    /// Ask before saving over or deleting a saved sound
+    #[serde(default = "default_confirm_overwrite")]
+    pub confirm_overwrite: bool,
+    /// Recently deleted or overwritten sounds, newest last
+    #[serde(default)]
+    pub trash: Vec<TrashedSound>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            theme: String::new(),
+            saved: Default::default(),
+            export_dir: None,
+            sprite_gap: DEFAULT_SPRITE_GAP,
+            export: ExportOptions::default(),
+            variation: VariationSettings::default(),
+            normalise_target: NormaliseTarget::default(),
+            max_length: DEFAULT_MAX_LENGTH,
+            current: None,
+            themes: vec![],
+            keys: BTreeMap::new(),
+            repeat: RepeatSettings::default(),
+            confirm_overwrite: true,
+            trash: vec![],
+        }
+    }
+}
+
+fn default_sprite_gap() -> f32 {
+    DEFAULT_SPRITE_GAP
+}
+
+fn default_max_length() -> f32 {
+    DEFAULT_MAX_LENGTH
+}
+
+fn default_confirm_overwrite() -> bool {
+    true
+}
+
+/// Theme name, older settings stored the index of a built in theme instead
+fn theme_name<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Stored {
+        Name(String),
+        Index(usize),
+    }
+    Ok(match Stored::deserialize(deserializer)? {
+        Stored::Name(name) => name,
+        Stored::Index(index) => themes()
+            .get(index)
+            .map(|theme| theme.name.clone())
+            .unwrap_or_default(),
+    })
+}
+
+/// Number of deleted sounds kept
+pub const TRASH_SIZE: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrashedSound {
+    /// Slot the sound was in, it's restored there if still empty
+    pub slot: usize,
+    pub sound: SoundSave,
+}
+
+impl Settings {
+    pub fn export_dir(&self) -> PathBuf {
+        self.export_dir
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_EXPORT_DIR))
+    }
+
+    /// See [SoundSave::clear_generated_name]
+    pub fn clear_generated_names(&mut self) {
+        self.saved
+            .iter_mut()
+            .flatten()
+            .chain(self.current.as_mut())
+            .chain(self.trash.iter_mut().map(|trashed| &mut trashed.sound))
+            .for_each(SoundSave::clear_generated_name);
+    }
+
+    /// Keep `sound` so it can be restored, dropping the oldest if the trash is full
+    pub fn trash(&mut self, slot: usize, sound: SoundSave) {
+        self.trash.push(TrashedSound { slot, sound });
+        if self.trash.len() > TRASH_SIZE {
+            self.trash.remove(0);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SoundSave {
+    /// Set by the user, may be empty, see [display_name](SoundSave::display_name)
     pub name: String,
+    #[serde(default)]
+    pub notes: String,
     pub when: DateTime<Utc>,
     pub volume: f32,
     pub attack: f32,
@@ -24,12 +159,51 @@ pub struct SoundSave {
     pub drive_enabled: bool,
     pub osc: OscillatorType,
     pub duty: DutyCycle,
+    #[serde(default)]
+    pub vibrato: f32,
+    #[serde(default)]
+    pub vibrato_enabled: bool,
+    #[serde(default)]
+    pub vibrato_rate: f32,
+    #[serde(default)]
+    pub vibrato_delay: f32,
+    #[serde(default)]
+    pub tremolo: f32,
+    #[serde(default)]
+    pub tremolo_enabled: bool,
+    #[serde(default)]
+    pub tremolo_rate: f32,
+    #[serde(default)]
+    pub tremolo_delay: f32,
+    #[serde(default)]
+    pub arpeggio: f32,
+    #[serde(default)]
+    pub arpeggio_enabled: bool,
+    #[serde(default)]
+    pub arpeggio_time: f32,
+    #[serde(default)]
+    pub repeat: f32,
+    #[serde(default)]
+    pub repeat_enabled: bool,
+    #[serde(default)]
+    pub snap_scale: usize,
+    #[serde(default)]
+    pub snap_enabled: bool,
+    #[serde(default)]
+    pub snap_root: usize,
+    #[serde(default = "default_tuning")]
+    pub tuning: f32,
+}
+
+fn default_tuning() -> f32 {
+    A4_FREQ
 }
 
 impl SoundSave {
     pub fn new_blank() -> Self {
         Self {
             name: String::new(),
+            notes: String::new(),
             when: Utc::now(),
             volume: 0.0,
             attack: 0.0,
@@ -43,10 +217,45 @@ impl SoundSave {
             drive_enabled: false,
             osc: OscillatorType::Sine,
             duty: DutyCycle::Half,
+            vibrato: 0.0,
+            vibrato_enabled: false,
+            vibrato_rate: 0.0,
+            vibrato_delay: 0.0,
+            tremolo: 0.0,
+            tremolo_enabled: false,
+            tremolo_rate: 0.0,
+            tremolo_delay: 0.0,
+            arpeggio: 0.0,
+            arpeggio_enabled: false,
+            arpeggio_time: 0.0,
+            repeat: 0.0,
+            repeat_enabled: false,
+            snap_scale: 0,
+            snap_enabled: false,
+            snap_root: 0,
+            tuning: A4_FREQ,
         }
     }
 
-    pub fn fix_name(&mut self) {
+    /// Name if set, otherwise the parameter summary
+    pub fn display_name(&self) -> String {
+        if self.name.trim().is_empty() {
+            self.summary()
+        } else {
+            self.name.clone()
+        }
+    }
+
+    /// Older versions stored the summary as the name, this removes it so the summary stays up
+    /// to date
+    pub fn clear_generated_name(&mut self) {
+        if self.name == self.summary() {
+            self.name.clear();
+        }
+    }
+
+    /// Oscillator, frequency, envelope, crunch, drive and duty cycle
+    pub fn summary(&self) -> String {
         let osc = match self.osc {
             OscillatorType::Sine => "SIN",
             OscillatorType::Saw => "SAW",
@@ -68,7 +277,7 @@ impl SoundSave {
             DutyCycle::Third => "1/3",
             DutyCycle::Half => "1/2",
         };
-        self.name = format!(
+        format!(
             "{} {: >4} {:.1} {:.1} {:.1} {:.1} {} {} {}",
             osc,
             self.freq,
@@ -91,4 +300,215 @@ impl SoundSave {
     pub fn freq(&self) -> usize {
         usize::try_from(self.freq).unwrap_or(500)
     }
+
+    pub fn create_sample(&self) -> Sample {
+        let mut sample = Sample::default();
+        sample
+            .osc_type(self.osc)
+            .osc_duty_cycle(self.duty)
+            .osc_frequency(self.freq())
+            .volume(self.volume)
+            .env_attack(self.attack)
+            .env_decay(self.decay)
+            .env_sustain(self.sustain)
+            .env_release(self.release);
+        if self.crunch_enabled {
+            sample.dis_crunch(self.crunch);
+        }
+        if self.drive_enabled {
+            sample.dis_drive(self.drive);
+        }
+        sample
+    }
+
+    pub fn envelope(&self) -> Envelope {
+        Envelope {
+            attack: self.attack,
+            decay: self.decay,
+            sustain: self.sustain,
+            release: self.release,
+        }
+    }
+
+    pub fn modulation(&self) -> Modulation {
+        Modulation {
+            vibrato: self.vibrato_enabled.then_some(Lfo {
+                rate: self.vibrato_rate,
+                depth: self.vibrato,
+                delay: self.vibrato_delay,
+            }),
+            tremolo: self.tremolo_enabled.then_some(Lfo {
+                rate: self.tremolo_rate,
+                depth: self.tremolo,
+                delay: self.tremolo_delay,
+            }),
+        }
+    }
+
+    pub fn sequence(&self) -> Sequence {
+        Sequence {
+            arpeggio: self.arpeggio_enabled.then_some(Arpeggio {
+                multiplier: self.arpeggio,
+                time: self.arpeggio_time,
+            }),
+            repeat: self.repeat_enabled.then_some(self.repeat),
+        }
+    }
+
+    pub fn tuning(&self) -> Tuning {
+        Tuning {
+            a4: self.tuning.max(1.0),
+            scale: self.snap_enabled.then_some(self.snap_scale),
+            root: self.snap_root as i32,
+        }
+    }
+
+    /// Short names of the parameters that aren't the same in `other`
+    pub fn differences(&self, other: &SoundSave) -> Vec<&'static str> {
+        let ne = |lhs: f32, rhs: f32| (lhs - rhs).abs() > 0.0001;
+        let checks = [
+            ("VOL", ne(self.volume, other.volume)),
+            ("ATK", ne(self.attack, other.attack)),
+            ("DEC", ne(self.decay, other.decay)),
+            ("SUS", ne(self.sustain, other.sustain)),
+            ("REL", ne(self.release, other.release)),
+            ("FREQ", self.freq != other.freq),
+            ("OSC", self.osc != other.osc),
+            ("DUTY", self.duty != other.duty),
+            (
+                "CRUNCH",
+                self.crunch_enabled != other.crunch_enabled || ne(self.crunch, other.crunch),
+            ),
+            (
+                "DRIVE",
+                self.drive_enabled != other.drive_enabled || ne(self.drive, other.drive),
+            ),
+            (
+                "VIB",
+                self.modulation().vibrato != other.modulation().vibrato,
+            ),
+            (
+                "TREM",
+                self.modulation().tremolo != other.modulation().tremolo,
+            ),
+            ("ARP", self.sequence().arpeggio != other.sequence().arpeggio),
+            ("REPEAT", self.sequence().repeat != other.sequence().repeat),
+            ("TUNING", self.tuning() != other.tuning()),
+        ];
+        checks
+            .into_iter()
+            .filter(|(_, differs)| *differs)
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// Change the volume so the sound reaches `target`
+    ///
+    /// Crunch and drive mean the level doesn't always follow the volume exactly, so this is
+    /// repeated a few times to get closer
+    pub fn normalise(&mut self, target: NormaliseTarget, max_length: f32) {
+        for _ in 0..4 {
+            let levels = Levels::measure(&self.create_data(max_length), SAMPLE_RATE);
+            let difference = target.difference(&levels);
+            if !difference.is_finite() || difference.abs() < 0.05 {
+                return;
+            }
+            self.volume *= 10_f32.powf(difference / 20.0);
+        }
+    }
+
+    /// Render the sound with the sequence and modulation applied, up to `max_length` seconds long
+    pub fn create_data(&self, max_length: f32) -> Vec<f32> {
+        self.create_data_at(self.freq(), max_length)
+    }
+
+    /// Render the sound at `freq` hz instead of its own frequency
+    pub fn create_data_at(&self, freq: usize, max_length: f32) -> Vec<f32> {
+        self.render(freq, max_length, &|| false).unwrap_or_default()
+    }
+
+    /// Render the sound like [SoundSave::create_data], giving up early if `cancelled` returns true
+    pub fn create_data_unless(&self, max_length: f32, cancelled: Cancelled) -> Option<Vec<f32>> {
+        self.render(self.freq(), max_length, cancelled)
+    }
+
+    fn render(&self, freq: usize, max_length: f32, cancelled: Cancelled) -> Option<Vec<f32>> {
+        let mut sample = self.create_sample();
+        sample.osc_frequency(freq);
+        let rate = SAMPLE_RATE as usize;
+        let data = self.sequence().render(
+            sample,
+            &self.envelope(),
+            freq,
+            rate,
+            max_samples(max_length, rate),
+            cancelled,
+        )?;
+        Some(self.modulation().apply(data, rate))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn theme_after_load(theme: serde_json::Value) -> String {
+        let mut json = serde_json::to_value(Settings::default()).unwrap();
+        json["theme"] = theme;
+        serde_json::from_value::<Settings>(json).unwrap().theme
+    }
+
+    #[test]
+    fn loads_theme_name_or_index() {
+        assert_eq!(theme_after_load("hacker".into()), "hacker");
+        assert_eq!(theme_after_load(1.into()), "Gameboy");
+        assert_eq!(theme_after_load(99.into()), "");
+    }
+
+    #[test]
+    fn display_name_falls_back_to_summary() {
+        let mut sound = SoundSave::new_blank();
+        assert_eq!(sound.display_name(), sound.summary());
+        sound.name = String::from("  ");
+        assert_eq!(sound.display_name(), sound.summary());
+        sound.name = String::from("Jump");
+        assert_eq!(sound.display_name(), "Jump");
+    }
+
+    #[test]
+    fn clears_only_generated_names() {
+        let mut sound = SoundSave::new_blank();
+        sound.name = sound.summary();
+        sound.clear_generated_name();
+        assert_eq!(sound.name, "");
+        sound.name = String::from("Jump");
+        sound.clear_generated_name();
+        assert_eq!(sound.name, "Jump");
+    }
+
+    #[test]
+    fn trash_drops_oldest() {
+        let mut settings = Settings::default();
+        for slot in 0..TRASH_SIZE + 2 {
+            let mut sound = SoundSave::new_blank();
+            sound.name = format!("sound {slot}");
+            settings.trash(slot, sound);
+        }
+        assert_eq!(settings.trash.len(), TRASH_SIZE);
+        let kept: Vec<(usize, &str)> = settings
+            .trash
+            .iter()
+            .map(|trashed| (trashed.slot, trashed.sound.name.as_str()))
+            .collect();
+        assert_eq!(
+            kept,
+            [
+                (2, "sound 2"),
+                (3, "sound 3"),
+                (4, "sound 4"),
+                (5, "sound 5"),
+                (6, "sound 6")
+            ]
+        );
+    }
 }
@@ -1,48 +1,216 @@
+use anyhow::{Context, Result};
 use pixels_graphics_lib::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
 
+/// File in the config dir that user themes are loaded from
+pub const THEME_FILE: &str = "themes.json";
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Theme {
+    pub name: String,
     pub active: Color,
     pub background: Color,
     pub disabled: Color,
     pub inactive: Color,
+    pub waveform: Color,
+    /// Used to highlight problems, such as clipping
+    pub warning: Color,
+}
+
+/// Theme as written in the theme file or settings, colours are hex strings such as `#FF8800` or
+/// `#FF880080`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThemeData {
+    pub name: String,
+    active: String,
+    background: String,
+    disabled: String,
+    inactive: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    waveform: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    warning: Option<String>,
+}
+
+impl From<&Theme> for ThemeData {
+    fn from(theme: &Theme) -> Self {
+        ThemeData {
+            name: theme.name.clone(),
+            active: theme.active.to_hex(),
+            background: theme.background.to_hex(),
+            disabled: theme.disabled.to_hex(),
+            inactive: theme.inactive.to_hex(),
+            waveform: Some(theme.waveform.to_hex()),
+            warning: Some(theme.warning.to_hex()),
+        }
+    }
+}
+
+impl TryFrom<ThemeData> for Theme {
+    type Error = anyhow::Error;
+
+    fn try_from(file: ThemeData) -> Result<Self> {
+        let color = |field: &str, hex: &str| {
+            Color::from_hex(hex)
+                .map_err(|err| anyhow::anyhow!("{err:?}"))
+                .with_context(|| format!("{} {field} '{hex}'", file.name))
+        };
+        let inactive = color("inactive", &file.inactive)?;
+        Ok(Theme {
+            active: color("active", &file.active)?,
+            background: color("background", &file.background)?,
+            disabled: color("disabled", &file.disabled)?,
+            waveform: match &file.waveform {
+                Some(hex) => color("waveform", hex)?,
+                None => inactive,
+            },
+            warning: match &file.warning {
+                Some(hex) => color("warning", hex)?,
+                None => RED,
+            },
+            inactive,
+            name: file.name,
+        })
+    }
+}
+
+fn theme(name: &str, active: Color, background: Color, disabled: Color, inactive: Color) -> Theme {
+    Theme {
+        name: name.to_string(),
+        active,
+        background,
+        disabled,
+        inactive,
+        waveform: inactive,
+        warning: RED,
+    }
 }
 
 pub fn themes() -> Vec<Theme> {
     vec![
-        //Dark
-        Theme {
-            active: WHITE,
-            background: BLACK,
-            disabled: WHITE.with_brightness(0.2),
-            inactive: WHITE.with_brightness(0.5),
-        },
-        //Gameboy
-        Theme {
-            active: GB_3,
-            background: GB_0,
-            disabled: GB_1,
-            inactive: GB_2,
-        },
-        // Hacker
-        Theme {
-            active: GREEN,
-            background: BLACK,
-            disabled: GREEN.with_brightness(0.2),
-            inactive: GREEN.with_brightness(0.5),
-        },
-        // New Vegas
-        Theme {
-            active: ORANGE,
-            background: BLACK,
-            disabled: ORANGE.with_brightness(0.2),
-            inactive: ORANGE.with_brightness(0.5),
-        },
-        // Light
-        Theme {
-            active: BLACK,
-            background: WHITE,
-            disabled: LIGHT_GRAY,
-            inactive: DARK_GRAY,
-        },
+        theme(
+            "Dark",
+            WHITE,
+            BLACK,
+            WHITE.with_brightness(0.2),
+            WHITE.with_brightness(0.5),
+        ),
+        theme("Gameboy", GB_3, GB_0, GB_1, GB_2),
+        theme(
+            "Hacker",
+            GREEN,
+            BLACK,
+            GREEN.with_brightness(0.2),
+            GREEN.with_brightness(0.5),
+        ),
+        theme(
+            "New Vegas",
+            ORANGE,
+            BLACK,
+            ORANGE.with_brightness(0.2),
+            ORANGE.with_brightness(0.5),
+        ),
+        theme("Light", BLACK, WHITE, LIGHT_GRAY, DARK_GRAY),
     ]
 }
+
+/// Built in themes along with any from [THEME_FILE] in `dir`
+pub fn load_themes(dir: Option<&Path>) -> Vec<Theme> {
+    let mut themes = themes();
+    let Some(dir) = dir else {
+        return themes;
+    };
+    let path = dir.join(THEME_FILE);
+    if !path.exists() {
+        return themes;
+    }
+    match read_themes(&path) {
+        Ok(user_themes) => merge_themes(&mut themes, user_themes),
+        Err(err) => eprintln!("unable to load themes from {}: {err:?}", path.display()),
+    }
+    themes
+}
+
+/// Add `extra` to `themes`, any with the same name as an existing theme replace it
+pub fn merge_themes(themes: &mut Vec<Theme>, extra: Vec<Theme>) {
+    for theme in extra {
+        match themes
+            .iter_mut()
+            .find(|existing| existing.name.eq_ignore_ascii_case(&theme.name))
+        {
+            Some(existing) => *existing = theme,
+            None => themes.push(theme),
+        }
+    }
+}
+
+/// Index of the theme called `name` (ignoring case), or the first theme if there isn't one
+pub fn find_theme(themes: &[Theme], name: &str) -> usize {
+    themes
+        .iter()
+        .position(|theme| theme.name.eq_ignore_ascii_case(name))
+        .unwrap_or_default()
+}
+
+/// Convert themes saved in settings, skipping any that are invalid
+pub fn saved_themes(data: &[ThemeData]) -> Vec<Theme> {
+    data.iter()
+        .cloned()
+        .filter_map(|data| {
+            Theme::try_from(data)
+                .map_err(|err| eprintln!("invalid saved theme: {err:?}"))
+                .ok()
+        })
+        .collect()
+}
+
+/// Themes in the file at `path`, skipping any that are invalid so one mistake doesn't lose the
+/// rest
+fn read_themes(path: &Path) -> Result<Vec<Theme>> {
+    let json = fs::read_to_string(path)?;
+    let files: Vec<ThemeData> = serde_json::from_str(&json)?;
+    Ok(files
+        .into_iter()
+        .filter_map(|data| {
+            Theme::try_from(data)
+                .map_err(|err| eprintln!("invalid theme in {}: {err:?}", path.display()))
+                .ok()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn finds_theme_ignoring_case() {
+        let mut themes = themes();
+        assert_eq!(find_theme(&themes, "new vegas"), 3);
+        assert_eq!(find_theme(&themes, "Missing"), 0);
+        let mut custom = themes[0].clone();
+        custom.name = String::from("Custom 1");
+        merge_themes(&mut themes, vec![custom]);
+        assert_eq!(find_theme(&themes, "CUSTOM 1"), 5);
+    }
+
+    #[test]
+    fn skips_invalid_themes_in_file() {
+        let path = std::env::temp_dir().join(format!("usfx_themes_{}.json", std::process::id()));
+        let json = r##"[
+            {"name": "Bad", "active": "#GG0000", "background": "#000000", "disabled": "#333333", "inactive": "#777777"},
+            {"name": "Good", "active": "#FF0000", "background": "#000000", "disabled": "#333333", "inactive": "#777777"}
+        ]"##;
+        fs::write(&path, json).unwrap();
+        let themes = read_themes(&path);
+        fs::remove_file(&path).unwrap();
+        let names: Vec<String> = themes
+            .unwrap()
+            .into_iter()
+            .map(|theme| theme.name)
+            .collect();
+        assert_eq!(names, ["Good"]);
+    }
+}
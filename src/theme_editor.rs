@@ -0,0 +1,414 @@
+use crate::audio::Audio;
+use crate::controller::Controller;
+use crate::export::ExportOptions;
+use crate::meter::NormaliseTarget;
+use crate::settings::SoundSave;
+use crate::theme::Theme;
+use crate::worker::WaveformWorker;
+use crate::{HEIGHT, SN, SR, WIDTH};
+use pixels_graphics_lib::buffer_graphics_lib::prelude::TextPos::Px;
+use pixels_graphics_lib::buffer_graphics_lib::prelude::*;
+use pixels_graphics_lib::prelude::PixelFont::{Standard4x5, Standard6x7};
+use pixels_graphics_lib::prelude::SceneUpdateResult::{Nothing, Pop};
+use pixels_graphics_lib::prelude::*;
+
+const PREVIEW_X: isize = 167;
+const PREVIEW_Y: isize = 4;
+const COLORS: [&str; 4] = ["ACTIVE", "BACKGROUND", "DISABLED", "INACTIVE"];
+/// Keys to decrease and increase each slider
+const SLIDER_KEYS: [(KeyCode, KeyCode); 3] = [
+    (KeyCode::KeyQ, KeyCode::KeyW),
+    (KeyCode::KeyA, KeyCode::KeyS),
+    (KeyCode::KeyZ, KeyCode::KeyX),
+];
+
+/// Everything the editor needs to show a preview of the main screen
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThemeEditorSetup {
+    pub themes: Vec<Theme>,
+    /// Index of the theme being edited
+    pub active: usize,
+    pub sound: SoundSave,
+    pub saves: [Option<SoundSave>; 10],
+    pub export: ExportOptions,
+    pub target: NormaliseTarget,
+    /// Longest sound to render, in seconds
+    pub max_length: f32,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Mode {
+    Rgb,
+    Hsv,
+}
+
+impl Mode {
+    fn labels(&self) -> [&'static str; 3] {
+        match self {
+            Mode::Rgb => ["RED", "GREEN", "BLUE"],
+            Mode::Hsv => ["HUE", "SAT", "VALUE"],
+        }
+    }
+
+    fn max(&self) -> [f32; 3] {
+        match self {
+            Mode::Rgb => [255.0; 3],
+            Mode::Hsv => [360.0, 100.0, 100.0],
+        }
+    }
+
+    /// Normal, big (shift) and small (control) steps
+    fn steps(&self) -> (f32, f32, f32) {
+        match self {
+            Mode::Rgb => (8.0, 32.0, 1.0),
+            Mode::Hsv => (5.0, 20.0, 1.0),
+        }
+    }
+}
+
+/// Edit the colours of a theme, on save the result is returned to the main scene
+pub struct ThemeEditorScene {
+    setup: ThemeEditorSetup,
+    theme: Theme,
+    /// The waveform colour follows inactive unless the theme had set it separately
+    waveform_follows: bool,
+    selected: usize,
+    mode: Mode,
+    /// Slider values for the selected colour, HSV is kept here as the hue would be lost when
+    /// saturation or value is 0
+    values: [f32; 3],
+    controller: Controller,
+    worker: WaveformWorker,
+    preview: Image,
+    preview_outdated: bool,
+    result: SceneUpdateResult<SR, SN>,
+}
+
+impl ThemeEditorScene {
+    pub fn new(setup: ThemeEditorSetup) -> Self {
+        let theme = setup.themes[setup.active].clone();
+        let mut controller = Controller::new(Audio::muted(), &theme);
+        controller.load(&setup.sound);
+        controller.max_length = setup.max_length;
+        controller.rendering = true;
+        let mut worker = WaveformWorker::new();
+        worker.request(setup.sound.clone(), None, false, setup.max_length);
+        let mut scene = ThemeEditorScene {
+            waveform_follows: theme.waveform == theme.inactive,
+            theme,
+            setup,
+            selected: 0,
+            mode: Mode::Rgb,
+            values: [0.0; 3],
+            controller,
+            worker,
+            preview: Image::new_blank(WIDTH / 2, HEIGHT / 2),
+            preview_outdated: true,
+            result: Nothing,
+        };
+        scene.update_values();
+        scene
+    }
+}
+
+impl ThemeEditorScene {
+    fn color(&self, idx: usize) -> Color {
+        match idx {
+            0 => self.theme.active,
+            1 => self.theme.background,
+            2 => self.theme.disabled,
+            _ => self.theme.inactive,
+        }
+    }
+
+    fn set_color(&mut self, idx: usize, color: Color) {
+        match idx {
+            0 => self.theme.active = color,
+            1 => self.theme.background = color,
+            2 => self.theme.disabled = color,
+            _ => {
+                self.theme.inactive = color;
+                if self.waveform_follows {
+                    self.theme.waveform = color;
+                }
+            }
+        }
+        self.controller.on_theme_change(&self.theme);
+        self.controller.theme_name_time = 0.0;
+        self.preview_outdated = true;
+    }
+
+    /// Read the slider values from the selected colour
+    fn update_values(&mut self) {
+        let color = self.color(self.selected);
+        self.values = match self.mode {
+            Mode::Rgb => [color.r as f32, color.g as f32, color.b as f32],
+            Mode::Hsv => to_hsv(color),
+        };
+    }
+
+    fn change_value(&mut self, slider: usize, amount: f32) {
+        let max = self.mode.max()[slider];
+        let value = self.values[slider] + amount;
+        self.values[slider] = if self.mode == Mode::Hsv && slider == 0 {
+            value.rem_euclid(max)
+        } else {
+            value.clamp(0.0, max)
+        };
+        let alpha = self.color(self.selected).a;
+        let color = match self.mode {
+            Mode::Rgb => Color::new(
+                self.values[0] as u8,
+                self.values[1] as u8,
+                self.values[2] as u8,
+                alpha,
+            ),
+            Mode::Hsv => from_hsv(self.values, alpha),
+        };
+        self.set_color(self.selected, color);
+    }
+
+    /// Render the main screen with the edited theme at half size
+    fn render_preview(&mut self) {
+        let mut themes = self.setup.themes.clone();
+        themes[self.setup.active] = self.theme.clone();
+        let mut buffer = Graphics::create_buffer_u8(WIDTH, HEIGHT);
+        if let Ok(mut graphics) = Graphics::new_u8_rgba(&mut buffer, WIDTH, HEIGHT) {
+            graphics.clear(self.theme.background);
+            self.controller.render(
+                &mut graphics,
+                &themes,
+                self.setup.active,
+                &self.setup.saves,
+                &self.setup.export,
+                &self.setup.target,
+            );
+        }
+        self.preview = half_size(&buffer, WIDTH, HEIGHT);
+    }
+}
+
+impl Scene<SR, SN> for ThemeEditorScene {
+    fn render(&self, graphics: &mut Graphics, _: &MouseData, _: &FxHashSet<KeyCode>) {
+        //drawn with the original theme so the editor stays readable whatever colours are picked
+        let theme = &self.setup.themes[self.setup.active];
+        graphics.clear(theme.background);
+        graphics.draw_text("THEME EDITOR", Px(4, 4), (theme.active, Standard6x7));
+        graphics.draw_text(
+            &self.theme.name.to_uppercase(),
+            Px(4, 14),
+            (theme.inactive, Standard4x5),
+        );
+
+        for (i, name) in COLORS.iter().enumerate() {
+            let y = 28 + i as isize * 14;
+            let text_color = if i == self.selected {
+                graphics.draw_text(">", Px(4, y), (theme.active, Standard4x5));
+                theme.active
+            } else {
+                theme.inactive
+            };
+            let color = self.color(i);
+            graphics.draw_text(name, Px(12, y), (text_color, Standard4x5));
+            graphics.draw_rect(Rect::new_with_size((68, y - 2), 14, 9), fill(color));
+            graphics.draw_rect(
+                Rect::new_with_size((68, y - 2), 14, 9),
+                stroke(theme.inactive),
+            );
+            graphics.draw_text(&color.to_hex()[..7], Px(88, y), (text_color, Standard4x5));
+        }
+
+        let (rgb, hsv) = match self.mode {
+            Mode::Rgb => (theme.active, theme.disabled),
+            Mode::Hsv => (theme.disabled, theme.active),
+        };
+        graphics.draw_text("[TAB]", Px(4, 90), (theme.inactive, Standard4x5));
+        graphics.draw_text("RGB", Px(34, 90), (rgb, Standard4x5));
+        graphics.draw_text("HSV", Px(54, 90), (hsv, Standard4x5));
+
+        let labels = self.mode.labels();
+        let max = self.mode.max();
+        for (i, (dec, inc)) in ['Q', 'A', 'Z'].iter().zip(['W', 'S', 'X']).enumerate() {
+            let y = 104 + i as isize * 22;
+            graphics.draw_text(
+                &format!("{dec}/{inc} {}", labels[i]),
+                Px(4, y),
+                (theme.inactive, Standard4x5),
+            );
+            graphics.draw_text(
+                &format!("{}", self.values[i].round()),
+                Px(150, y),
+                (theme.active, Standard4x5, Positioning::RightTop),
+            );
+            let width = 146;
+            graphics.draw_rect(
+                Rect::new_with_size((4, y + 8), width, 6),
+                stroke(theme.inactive),
+            );
+            let filled = (self.values[i] / max[i] * width as f32).round() as usize;
+            if filled > 0 {
+                graphics.draw_rect(
+                    Rect::new_with_size((4, y + 8), filled, 6),
+                    fill(theme.active),
+                );
+            }
+        }
+
+        graphics.draw_rect(
+            Rect::new_with_size(
+                (PREVIEW_X - 1, PREVIEW_Y - 1),
+                WIDTH / 2 + 1,
+                HEIGHT / 2 + 1,
+            ),
+            stroke(theme.inactive),
+        );
+        graphics.draw_image((PREVIEW_X, PREVIEW_Y), &self.preview);
+
+        let help = [
+            "[UP/DOWN] SELECT COLOUR",
+            "[TAB] SWITCH BETWEEN RGB AND HSV",
+            "Q/W A/S Z/X CHANGE VALUES",
+            "HOLD SHIFT FOR BIGGER, CONTROL FOR SMALLER",
+            "",
+            "[ENTER] SAVE AS CUSTOM THEME",
+            "[ESC] CANCEL",
+        ];
+        for (i, line) in help.iter().enumerate() {
+            graphics.draw_text(
+                line,
+                Px(4, 210 + i as isize * 9),
+                (theme.inactive, Standard4x5),
+            );
+        }
+    }
+
+    fn on_key_down(&mut self, key: KeyCode, _: &MouseData, held_keys: &FxHashSet<KeyCode>) {
+        match key {
+            KeyCode::Escape => self.result = Pop(None),
+            KeyCode::Enter | KeyCode::NumpadEnter => {
+                self.result = Pop(Some(SR::Theme(self.theme.clone())))
+            }
+            KeyCode::ArrowUp => {
+                self.selected = self.selected.saturating_sub(1);
+                self.update_values();
+            }
+            KeyCode::ArrowDown => {
+                self.selected = (self.selected + 1).min(COLORS.len() - 1);
+                self.update_values();
+            }
+            KeyCode::Tab => {
+                self.mode = match self.mode {
+                    Mode::Rgb => Mode::Hsv,
+                    Mode::Hsv => Mode::Rgb,
+                };
+                self.update_values();
+            }
+            _ => {
+                let (normal, big, small) = self.mode.steps();
+                let step = if held_keys.contains(&KeyCode::ShiftLeft)
+                    || held_keys.contains(&KeyCode::ShiftRight)
+                {
+                    big
+                } else if held_keys.contains(&KeyCode::ControlLeft)
+                    || held_keys.contains(&KeyCode::ControlRight)
+                {
+                    small
+                } else {
+                    normal
+                };
+                for (slider, (dec, inc)) in SLIDER_KEYS.iter().enumerate() {
+                    if key == *dec {
+                        self.change_value(slider, -step);
+                    } else if key == *inc {
+                        self.change_value(slider, step);
+                    }
+                }
+            }
+        }
+    }
+
+    fn update(
+        &mut self,
+        _: &Timing,
+        _: &MouseData,
+        _: &FxHashSet<KeyCode>,
+        _: &Window,
+    ) -> SceneUpdateResult<SR, SN> {
+        match self.worker.poll() {
+            Ok(Some(rendered)) => {
+                self.controller.waveform = rendered.waveform;
+                self.controller.levels = rendered.levels;
+                self.controller.rendering = false;
+                self.preview_outdated = true;
+            }
+            Ok(None) => {}
+            Err(err) => {
+                eprintln!("rendering failed: {err:?}");
+                self.controller.render_failed();
+                self.preview_outdated = true;
+            }
+        }
+        if self.preview_outdated {
+            self.preview_outdated = false;
+            self.render_preview();
+        }
+        self.result.clone()
+    }
+}
+
+/// Shrink an RGBA buffer to half its size, averaging each 2x2 block so thin lines stay visible
+fn half_size(buffer: &[u8], width: usize, height: usize) -> Image {
+    let (half_width, half_height) = (width / 2, height / 2);
+    let mut pixels = Vec::with_capacity(half_width * half_height);
+    for y in 0..half_height {
+        for x in 0..half_width {
+            let mut sum = [0_u32; 4];
+            for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                let i = ((y * 2 + dy) * width + x * 2 + dx) * 4;
+                for (channel, total) in sum.iter_mut().enumerate() {
+                    *total += buffer[i + channel] as u32;
+                }
+            }
+            let [r, g, b, a] = sum.map(|total| (total / 4) as u8);
+            pixels.push(Color::new(r, g, b, a));
+        }
+    }
+    Image::new(pixels, half_width, half_height)
+        .unwrap_or_else(|_| Image::new_blank(half_width, half_height))
+}
+
+/// Hue in degrees, saturation and value as percentages
+fn to_hsv(color: Color) -> [f32; 3] {
+    let [r, g, b] = [color.r, color.g, color.b].map(|c| c as f32 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+    let hue = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    let saturation = if max == 0.0 { 0.0 } else { delta / max };
+    [hue, saturation * 100.0, max * 100.0]
+}
+
+fn from_hsv(hsv: [f32; 3], alpha: u8) -> Color {
+    let [hue, saturation, value] = [hsv[0], hsv[1] / 100.0, hsv[2] / 100.0];
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - ((hue / 60.0).rem_euclid(2.0) - 1.0).abs());
+    let (r, g, b) = match (hue / 60.0) as usize {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = value - chroma;
+    let [r, g, b] = [r, g, b].map(|c| ((c + m) * 255.0).round() as u8);
+    Color::new(r, g, b, alpha)
+}
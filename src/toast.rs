@@ -0,0 +1,95 @@
+use crate::theme::Theme;
+use pixels_graphics_lib::buffer_graphics_lib::prelude::TextPos::Px;
+use pixels_graphics_lib::buffer_graphics_lib::prelude::*;
+use pixels_graphics_lib::prelude::PixelFont::Standard4x5;
+use std::collections::VecDeque;
+
+/// Seconds a message is shown for, including fading out
+const TOAST_TIME: f64 = 2.5;
+/// Seconds at the end of `TOAST_TIME` spent fading out
+const FADE_TIME: f64 = 0.5;
+/// Older messages are dropped when there are more than this
+const MAX_TOASTS: usize = 3;
+/// Longer messages are cut off to fit the window
+const MAX_CHARS: usize = 64;
+const TOAST_HEIGHT: isize = 11;
+/// Bottom of the newest message, over the waveform
+const BOTTOM: isize = 336;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum ToastKind {
+    Info,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+struct Toast {
+    message: String,
+    kind: ToastKind,
+    /// Seconds until it's removed
+    remaining: f64,
+}
+
+/// Short messages shown over the UI for a couple of seconds
+#[derive(Debug, Default)]
+pub struct Toasts {
+    toasts: VecDeque<Toast>,
+}
+
+impl Toasts {
+    pub fn info<S: Into<String>>(&mut self, message: S) {
+        self.push(message.into(), ToastKind::Info);
+    }
+
+    pub fn error<S: Into<String>>(&mut self, message: S) {
+        self.push(message.into(), ToastKind::Error);
+    }
+
+    fn push(&mut self, message: String, kind: ToastKind) {
+        self.toasts.push_back(Toast {
+            message,
+            kind,
+            remaining: TOAST_TIME,
+        });
+        while self.toasts.len() > MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+    }
+
+    pub fn update(&mut self, delta: f64) {
+        for toast in &mut self.toasts {
+            toast.remaining -= delta;
+        }
+        self.toasts.retain(|toast| toast.remaining > 0.0);
+    }
+
+    pub fn render(&self, graphics: &mut Graphics, theme: &Theme) {
+        for (i, toast) in self.toasts.iter().rev().enumerate() {
+            let alpha = (toast.remaining / FADE_TIME).min(1.0);
+            let alpha = |color: Color| color.with_alpha((color.a as f64 * alpha) as u8);
+            let text: String = toast
+                .message
+                .to_uppercase()
+                .chars()
+                .take(MAX_CHARS)
+                .collect();
+            let half_width = text.chars().count() as isize * 5 / 2 + 6;
+            let bottom = BOTTOM - i as isize * (TOAST_HEIGHT + 2);
+            let rect = Rect::new(
+                (170 - half_width, bottom - TOAST_HEIGHT),
+                (170 + half_width, bottom),
+            );
+            let color = match toast.kind {
+                ToastKind::Info => theme.active,
+                ToastKind::Error => theme.warning,
+            };
+            graphics.draw_rect(rect.clone(), fill(alpha(theme.background)));
+            graphics.draw_rect(rect, stroke(alpha(color)));
+            graphics.draw_text(
+                &text,
+                Px(170, bottom - TOAST_HEIGHT + 3),
+                (alpha(color), Standard4x5, Positioning::CenterTop),
+            );
+        }
+    }
+}
@@ -1,4 +1,8 @@
 use crate::controller::*;
+use crate::export::ExportOptions;
+use crate::keymap::{Action, KeyMap};
+use crate::meter::{format_db, Levels, NormaliseTarget};
+use crate::notes::Tuning;
 use crate::settings::SoundSave;
 use crate::theme::Theme;
 use crate::waveform::Waveform;
@@ -6,17 +10,22 @@ use indexmap::{indexmap, IndexMap};
 use pixels_graphics_lib::buffer_graphics_lib::clipping::Clip;
 use pixels_graphics_lib::buffer_graphics_lib::prelude::TextPos::Px;
 use pixels_graphics_lib::buffer_graphics_lib::prelude::*;
+use pixels_graphics_lib::prelude::KeyCode;
 use pixels_graphics_lib::prelude::PixelFont::{Limited3x5, Standard4x5, Standard6x7, Standard8x10};
 use usfx::*;
 
 pub fn render_ui(
     controller: &Controller,
     graphics: &mut Graphics,
-    theme: &Theme,
+    themes: &[Theme],
     active_theme: usize,
-    waveform: &Waveform,
     saves: &[Option<SoundSave>],
+    export: &ExportOptions,
+    target: &NormaliseTarget,
 ) {
+    let theme = &themes[active_theme];
+    let keys = &controller.keys;
+    let waveform = &controller.waveform;
     controller.shapes.render(graphics);
     for text in &controller.texts {
         graphics.draw(text);
@@ -36,9 +45,24 @@ pub fn render_ui(
         }
     }
 
+    let tuning = controller.tuning();
     let mut y = 50;
-    for (item, value) in &controller.items {
-        draw_item(graphics, theme, item, value, 4, y, &controller.button_shape);
+    for (item, value) in controller
+        .items
+        .iter()
+        .filter(|(item, _)| item.page == controller.page)
+    {
+        draw_item(
+            graphics,
+            theme,
+            keys,
+            item,
+            value,
+            &tuning,
+            4,
+            y,
+            &controller.button_shape,
+        );
         y += 16;
     }
 
@@ -46,7 +70,7 @@ pub fn render_ui(
 
     draw_button(
         graphics,
-        'I',
+        keys.label(Action::Oscillator(OscillatorType::Sine)),
         6,
         198,
         theme.inactive,
@@ -54,7 +78,7 @@ pub fn render_ui(
     );
     draw_button(
         graphics,
-        'O',
+        keys.label(Action::Oscillator(OscillatorType::Triangle)),
         66,
         198,
         theme.inactive,
@@ -62,7 +86,7 @@ pub fn render_ui(
     );
     draw_button(
         graphics,
-        'P',
+        keys.label(Action::Oscillator(OscillatorType::Saw)),
         166,
         198,
         theme.inactive,
@@ -70,7 +94,7 @@ pub fn render_ui(
     );
     draw_button(
         graphics,
-        'K',
+        keys.label(Action::Oscillator(OscillatorType::Square)),
         6,
         216,
         theme.inactive,
@@ -78,7 +102,7 @@ pub fn render_ui(
     );
     draw_button(
         graphics,
-        'L',
+        keys.label(Action::Oscillator(OscillatorType::Noise)),
         86,
         216,
         theme.inactive,
@@ -86,7 +110,7 @@ pub fn render_ui(
     );
     draw_button(
         graphics,
-        'B',
+        keys.label(Action::Duty(DutyCycle::Half)),
         6,
         252,
         theme.inactive,
@@ -94,7 +118,7 @@ pub fn render_ui(
     );
     draw_button(
         graphics,
-        'N',
+        keys.label(Action::Duty(DutyCycle::Third)),
         74,
         252,
         theme.inactive,
@@ -102,7 +126,7 @@ pub fn render_ui(
     );
     draw_button(
         graphics,
-        'M',
+        keys.label(Action::Duty(DutyCycle::Quarter)),
         6,
         272,
         theme.inactive,
@@ -110,35 +134,185 @@ pub fn render_ui(
     );
     draw_button(
         graphics,
-        ',',
+        keys.label(Action::Duty(DutyCycle::Eight)),
         74,
         272,
         theme.inactive,
         &controller.button_shape,
     );
-    draw_theme(graphics, theme, active_theme);
+    draw_theme(
+        graphics,
+        themes,
+        keys,
+        active_theme,
+        controller.theme_name_time > 0.0,
+    );
+    draw_page(graphics, theme, keys, controller.page);
+    draw_input_mode(
+        graphics,
+        theme,
+        keys,
+        controller.input_mode,
+        controller.octave,
+    );
 
-    draw_waveform(graphics, theme, waveform);
-    draw_duration(graphics, theme, waveform);
+    draw_waveform(
+        graphics,
+        theme,
+        keys,
+        waveform,
+        controller.compare_waveform.as_ref(),
+        controller.rendering,
+    );
+    draw_registers(graphics, theme, controller);
+    draw_export(graphics, theme, keys, export);
+    draw_duration(graphics, theme, waveform, &controller.levels);
+    draw_normalise(graphics, theme, keys, target);
 
     saves.iter().take(10).enumerate().for_each(|(i, save)| {
         if let Some(save) = save {
             graphics.with_translate(coord!(225, 40 + (i * 22)), |g| {
-                draw_save(g, theme, i + 1, &save.name, &save.formatted_when());
+                draw_save(g, theme, i + 1, save);
             });
         }
     });
 }
 
-fn draw_waveform(graphics: &mut Graphics, theme: &Theme, waveform: &Waveform) {
+fn draw_waveform(
+    graphics: &mut Graphics,
+    theme: &Theme,
+    keys: &KeyMap,
+    waveform: &Waveform,
+    compare: Option<&Waveform>,
+    rendering: bool,
+) {
+    if rendering {
+        graphics.draw_text(
+            "RENDERING...",
+            Px(170, 318),
+            (theme.inactive, Standard4x5, Positioning::Center),
+        );
+        return;
+    }
     graphics.with_translate(coord!(3, 297), |graphics| {
         graphics.set_clip(Clip::new(337, 48));
-        waveform.render_line(graphics, theme.inactive);
+        waveform.render_grid(graphics, theme.disabled);
+        if let Some(compare) = compare {
+            compare.render_line(graphics, theme.disabled);
+        }
+        waveform.render_line(graphics, theme.waveform);
+        waveform.render_clipping(graphics, theme.warning);
         graphics.clip_mut().set_all_valid();
     });
+    let mode = if waveform.absolute { "ABS" } else { "FIT" };
+    graphics.draw_text(
+        &format!("[{}] {mode}", keys.label(Action::WaveformMode)),
+        Px(5, 299),
+        (theme.inactive, Limited3x5),
+    );
+    if waveform.clipped > 0 {
+        graphics.draw_text(
+            &format!("CLIPPED {}", waveform.clipped),
+            Px(336, 299),
+            (theme.warning, Limited3x5, Positioning::RightTop),
+        );
+    }
 }
 
-fn draw_duration(graphics: &mut Graphics, theme: &Theme, waveform: &Waveform) {
+fn draw_export(graphics: &mut Graphics, theme: &Theme, keys: &KeyMap, export: &ExportOptions) {
+    graphics.draw_text(
+        &format!(
+            "{} {}",
+            keys.label(Action::NextFormat),
+            export.format.name().to_uppercase()
+        ),
+        Px(291, 5),
+        (theme.inactive, Limited3x5),
+    );
+    graphics.draw_text(
+        &format!("{} {}HZ", keys.label(Action::NextRate), export.sample_rate),
+        Px(291, 11),
+        (theme.inactive, Limited3x5),
+    );
+    let current = export
+        .source
+        .map(|source| source.name())
+        .unwrap_or("current");
+    graphics.draw_text(
+        &format!(
+            "{} {}",
+            keys.label(Action::ExportCurrent),
+            current.to_uppercase()
+        ),
+        Px(295, 32),
+        (theme.inactive, Limited3x5),
+    );
+}
+
+fn draw_registers(graphics: &mut Graphics, theme: &Theme, controller: &Controller) {
+    let keys = &controller.keys;
+    graphics.draw_text(
+        &format!(
+            "{} {} REG",
+            keys.label(Action::PrevRegister),
+            keys.label(Action::NextRegister)
+        ),
+        Px(226, 266),
+        (theme.inactive, Standard4x5),
+    );
+    for (i, letter) in ['A', 'B', 'C', 'D'].into_iter().enumerate() {
+        let x = 270 + i as isize * 17;
+        let rect = Rect::new_with_size((x, 264), 10, 10);
+        if i == controller.active_register {
+            graphics.draw_rect(rect, fill(theme.active));
+            graphics.draw_letter((x + 3, 267), letter, Standard4x5, theme.background);
+        } else {
+            let color = if i == controller.compare_register {
+                theme.active
+            } else if controller.registers[i].is_some() {
+                theme.inactive
+            } else {
+                theme.disabled
+            };
+            graphics.draw_rect(rect, stroke(color));
+            graphics.draw_letter((x + 3, 267), letter, Standard4x5, color);
+        }
+    }
+    if controller.compare_sound().is_some() {
+        let text = if controller.differences.is_empty() {
+            String::from("SAME")
+        } else {
+            controller.differences.join(" ")
+        };
+        let text: String = text.chars().take(27).collect();
+        graphics.draw_text(&text, Px(226, 277), (theme.inactive, Limited3x5));
+    }
+}
+
+fn draw_normalise(graphics: &mut Graphics, theme: &Theme, keys: &KeyMap, target: &NormaliseTarget) {
+    let (mode, level) = match target {
+        NormaliseTarget::Peak(level) => ("PEAK", level),
+        NormaliseTarget::Loudness(level) => ("LUFS", level),
+    };
+    graphics.draw_text(
+        &format!("{} NORM {mode} {level:.0}", keys.label(Action::Normalise)),
+        Px(40, 290),
+        (theme.inactive, Limited3x5),
+    );
+}
+
+/// Duration of the sound, with its levels to the left
+fn draw_duration(graphics: &mut Graphics, theme: &Theme, waveform: &Waveform, levels: &Levels) {
+    graphics.draw_text(
+        &format!(
+            "PEAK {}  RMS {}  LUFS {}",
+            format_db(levels.peak),
+            format_db(levels.rms),
+            format_db(levels.loudness)
+        ),
+        Px(256, 290),
+        (theme.active, Limited3x5, Positioning::RightTop),
+    );
     graphics.draw_text(
         &format!("{:.1}", waveform.duration),
         Px(333, 296),
@@ -151,11 +325,14 @@ fn draw_duration(graphics: &mut Graphics, theme: &Theme, waveform: &Waveform) {
     );
 }
 
+#[allow(clippy::too_many_arguments)]
 fn draw_item(
     graphics: &mut Graphics,
     theme: &Theme,
+    keys: &KeyMap,
     item: &Item,
     value: &State,
+    tuning: &Tuning,
     x: usize,
     y: usize,
     button_shape: &Drawable<Rect>,
@@ -165,11 +342,14 @@ fn draw_item(
     } else {
         (theme.disabled, theme.disabled)
     };
-    if let Some(tog) = item.toggle {
-        draw_button(graphics, tog, x, y, theme.inactive, button_shape);
+    if item.toggle {
+        let label = keys.label(Action::Toggle(item.name));
+        draw_button(graphics, label, x, y, theme.inactive, button_shape);
     }
-    draw_button(graphics, item.dec, x + 16, y, bcolor, button_shape);
-    draw_button(graphics, item.inc, x + 34, y, bcolor, button_shape);
+    let dec = keys.label(Action::Decrease(item.name));
+    let inc = keys.label(Action::Increase(item.name));
+    draw_button(graphics, dec, x + 16, y, bcolor, button_shape);
+    draw_button(graphics, inc, x + 34, y, bcolor, button_shape);
     graphics.draw(&Text::new(
         item.name,
         Px(x as isize + 50, y as isize),
@@ -177,27 +357,43 @@ fn draw_item(
     ));
     let text = match item.item_type {
         ItemType::Float => format!("{:0.2}", value.num()),
-        ItemType::Int => format!("{}", value.num().round() as usize),
+        ItemType::Int | ItemType::Pitch => format!("{}", value.num().round() as usize),
+        ItemType::Choice(choices) => choices[value.num() as usize].to_string(),
     };
+    if item.item_type == ItemType::Pitch {
+        graphics.draw_text(
+            &tuning.describe(value.num()),
+            Px(x as isize + 100, y as isize + 3),
+            (theme.inactive, Standard4x5),
+        );
+    }
     graphics.draw(&Text::new(
         &text,
-        Px(x as isize + 150, y as isize),
+        Px(x as isize + 156, y as isize),
         (vcolor, Standard8x10),
     ));
 }
 
+/// Labels longer than a single letter, such as `F5`, are drawn smaller to fit
 fn draw_button(
     graphics: &mut Graphics,
-    letter: char,
+    label: &str,
     x: usize,
     y: usize,
     color: Color,
     shape: &Drawable<Rect>,
 ) {
-    graphics.draw_letter((x as isize, y as isize), letter, Standard8x10, color);
-    shape
-        .with_move((x as isize - 2, y as isize - 3))
-        .render(graphics);
+    let (x, y) = (x as isize, y as isize);
+    if label.chars().count() == 1 {
+        graphics.draw_text(label, Px(x, y), (color, Standard8x10));
+    } else {
+        graphics.draw_text(
+            label,
+            Px(x + 4, y + 4),
+            (color, Limited3x5, Positioning::Center),
+        );
+    }
+    shape.with_move((x - 2, y - 3)).render(graphics);
 }
 
 pub fn generate_shapes(theme: &Theme) -> ShapeCollection {
@@ -288,10 +484,16 @@ pub fn generate_shapes(theme: &Theme) -> ShapeCollection {
         Line::new((223, 38), (338, 38)),
         stroke(general_color),
     );
+    //registers box
+    InsertShape::insert_above(
+        &mut collection,
+        Rect::new((223, 262), (338, 284)),
+        stroke(general_color),
+    );
     collection
 }
 
-pub fn generate_text(theme: &Theme) -> Vec<Text> {
+pub fn generate_text(theme: &Theme, keys: &KeyMap) -> Vec<Text> {
     let general_text_color = theme.inactive;
     vec![
         Text::new("TOGGLE", Px(60, 6), (general_text_color, Limited3x5)),
@@ -308,6 +510,11 @@ pub fn generate_text(theme: &Theme) -> Vec<Text> {
             Px(98, 35),
             (general_text_color, Limited3x5),
         ),
+        Text::new(
+            &format!("{} FOR HELP", keys.label(Action::Help)),
+            Px(98, 41),
+            (general_text_color, Limited3x5),
+        ),
         Text::new("Oscillator", Px(4, 182), (general_text_color, Standard8x10)),
         Text::new("Duty Cycle", Px(4, 236), (general_text_color, Standard8x10)),
         Text::new(
@@ -316,13 +523,31 @@ pub fn generate_text(theme: &Theme) -> Vec<Text> {
             (general_text_color, Standard4x5),
         ),
         Text::new(
-            "Space to play",
-            Px(65, 351),
-            (general_text_color, Standard8x10),
+            &format!("{} to play", keys.key_name(Action::Play)),
+            Px(129, 351),
+            (general_text_color, Standard8x10, Positioning::CenterTop),
         ),
         Text::new("Saved", Px(225, 6), (general_text_color, Standard8x10)),
         Text::new("WAVEFORM", Px(4, 290), (general_text_color, Limited3x5)),
-        Text::new("1-9 TO SAVE", Px(225, 20), (general_text_color, Limited3x5)),
+        Text::new(
+            &format!(
+                "{}-{} TO SAVE",
+                keys.label(Action::SaveSlot(0)),
+                keys.label(Action::SaveSlot(8))
+            ),
+            Px(225, 20),
+            (general_text_color, Limited3x5),
+        ),
+        Text::new(
+            &format!("{} EXPORT", keys.label(Action::ExportAll)),
+            Px(295, 20),
+            (general_text_color, Limited3x5),
+        ),
+        Text::new(
+            &format!("{} SPRITE", keys.label(Action::ExportSprite)),
+            Px(295, 26),
+            (general_text_color, Limited3x5),
+        ),
         Text::new(
             "+SHIFT TO LOAD",
             Px(225, 26),
@@ -358,18 +583,116 @@ pub fn duty_text(theme: &Theme) -> IndexMap<DutyCycle, Text> {
     ]
 }
 
-pub fn draw_theme(graphics: &mut Graphics, theme: &Theme, active: usize) {
+pub fn draw_page(graphics: &mut Graphics, theme: &Theme, keys: &KeyMap, page: Page) {
+    graphics.draw_text(
+        &format!("[{}] PAGE", keys.label(Action::NextPage)),
+        Px(160, 6),
+        (theme.inactive, Standard4x5),
+    );
+    graphics.draw_text(page.name(), Px(160, 13), (theme.active, Standard4x5));
+}
+
+pub fn draw_input_mode(
+    graphics: &mut Graphics,
+    theme: &Theme,
+    keys: &KeyMap,
+    mode: InputMode,
+    octave: i32,
+) {
+    graphics.draw_text(
+        &format!("[{}] KEYS", keys.label(Action::KeyboardMode)),
+        Px(4, 346),
+        (theme.inactive, Standard4x5),
+    );
+    match mode {
+        InputMode::Parameters => {
+            graphics.draw_text("EDIT", Px(4, 353), (theme.active, Standard4x5));
+        }
+        InputMode::Keyboard => {
+            graphics.draw_text(
+                &format!("PIANO C{octave}"),
+                Px(4, 353),
+                (theme.active, Standard4x5),
+            );
+            graphics.draw_text("UP/DOWN OCT", Px(4, 360), (theme.inactive, Limited3x5));
+        }
+    }
+}
+
+/// Draw `lines` in a box over the middle of the screen
+pub fn draw_prompt(graphics: &mut Graphics, theme: &Theme, lines: &[&str]) {
+    let height = lines.len() as isize * 8 + 12;
+    let top = 185 - height / 2;
+    let rect = Rect::new((60, top), (280, top + height));
+    graphics.draw_rect(rect.clone(), fill(theme.background));
+    graphics.draw_rect(rect, stroke(theme.active));
+    for (i, line) in lines.iter().enumerate() {
+        graphics.draw_text(
+            line,
+            Px(170, top + 8 + i as isize * 8),
+            (theme.active, Standard4x5, Positioning::CenterTop),
+        );
+    }
+}
+
+/// Draw the theme selector, or the name of the active theme if it was just changed
+pub fn draw_theme(
+    graphics: &mut Graphics,
+    themes: &[Theme],
+    keys: &KeyMap,
+    active: usize,
+    show_name: bool,
+) {
+    let theme = &themes[active];
+    if show_name {
+        let name: String = theme.name.to_uppercase().chars().take(27).collect();
+        graphics.draw_text(
+            &name,
+            Px(337, 346),
+            (theme.active, Standard4x5, Positioning::RightTop),
+        );
+    } else {
+        let (prev, next) = (Action::PrevTheme, Action::NextTheme);
+        let text = if keys.is(KeyCode::ArrowLeft, prev) && keys.is(KeyCode::ArrowRight, next) {
+            String::from("[ARROWS] THEME")
+        } else {
+            format!("[{} {}] THEME", keys.label(prev), keys.label(next))
+        };
+        graphics.draw_text(
+            &text,
+            Px(337, 346),
+            (theme.inactive, Standard4x5, Positioning::RightTop),
+        );
+    }
+    graphics.draw_text(
+        &format!("{} EDIT", keys.label(Action::ThemeEditor)),
+        Px(226, 358),
+        (theme.inactive, Standard4x5),
+    );
     graphics.draw_text(
-        "[ARROWS] THEME",
-        Px(267, 346),
+        &format!("{} BIND", keys.label(Action::KeyBindings)),
+        Px(226, 364),
         (theme.inactive, Standard4x5),
     );
-    let width = 60;
-    let count = 3;
-    let padding = 6;
-    let offset = (width - padding * 2) / count;
-    for i in 0..=4 {
-        draw_theme_box(graphics, theme, i, active == i, offset);
+    let width = 70;
+    let box_size = 6;
+    let count = themes.len();
+    let offset = if count > 1 {
+        ((width - box_size) / (count - 1)).min(16)
+    } else {
+        0
+    };
+    if offset > box_size {
+        for i in 0..count {
+            draw_theme_box(graphics, theme, i, active == i, offset);
+        }
+    } else {
+        //too many themes to fit boxes
+        graphics.draw_text(
+            &format!("{}/{count}", active + 1),
+            Px(267, 358),
+            (theme.inactive, Standard4x5),
+        );
     }
 }
 
@@ -391,7 +714,13 @@ pub fn draw_theme_box(
     );
 }
 
-pub fn draw_save(graphics: &mut Graphics, theme: &Theme, mut idx: usize, name: &str, when: &str) {
+/// Characters that fit on a line of a saved sound
+const SAVE_LINE_CHARS: usize = 19;
+/// Length of the oscillator, frequency, attack and decay part of the summary
+const SUMMARY_SPLIT: usize = 16;
+
+/// Draw a saved sound's name, or summary if it doesn't have one, and when it was saved
+pub fn draw_save(graphics: &mut Graphics, theme: &Theme, mut idx: usize, save: &SoundSave) {
     graphics.draw_rect(Rect::new_with_size((0, 0), 13, 20), stroke(theme.active));
     graphics.draw_rect(Rect::new_with_size((0, 0), 111, 20), stroke(theme.active));
     if idx == 10 {
@@ -399,8 +728,83 @@ pub fn draw_save(graphics: &mut Graphics, theme: &Theme, mut idx: usize, name: &
         idx = 0;
     }
     graphics.draw_text(&format!("{idx}"), Px(3, 5), (theme.active, Standard8x10));
-    let (part1, part2) = name.split_at(16);
-    graphics.draw_text(part1, Px(16, 2), (theme.inactive, Standard4x5));
-    graphics.draw_text(part2.trim(), Px(16, 8), (theme.inactive, Standard4x5));
-    graphics.draw_text(when, Px(16, 14), (theme.inactive, Standard4x5));
+    let (line1, line2) = save_lines(save);
+    graphics.draw_text(&line1, Px(16, 2), (theme.inactive, Standard4x5));
+    graphics.draw_text(&line2, Px(16, 8), (theme.inactive, Standard4x5));
+    graphics.draw_text(
+        &save.formatted_when(),
+        Px(16, 14),
+        (theme.inactive, Standard4x5),
+    );
+}
+
+/// The two lines of text shown for a saved sound, the name and start of the summary, or the
+/// whole summary split in two
+///
+/// Names are cut by character rather than byte so any length or character is safe
+fn save_lines(save: &SoundSave) -> (String, String) {
+    let fit = |text: &str, skip: usize, take: usize| -> String {
+        let rest: String = text.chars().skip(skip).collect();
+        rest.trim().to_uppercase().chars().take(take).collect()
+    };
+    let summary = save.summary();
+    if save.name.trim().is_empty() {
+        (
+            fit(&summary, 0, SUMMARY_SPLIT),
+            fit(&summary, SUMMARY_SPLIT, SAVE_LINE_CHARS),
+        )
+    } else {
+        (
+            fit(&save.name, 0, SAVE_LINE_CHARS),
+            fit(&summary, 0, SUMMARY_SPLIT),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::theme::themes;
+
+    fn named(name: &str) -> SoundSave {
+        let mut save = SoundSave::new_blank();
+        save.name = name.to_string();
+        save
+    }
+
+    #[test]
+    fn short_name() {
+        let save = named("Hi");
+        let summary: String = save.summary().chars().take(SUMMARY_SPLIT).collect();
+        assert_eq!(
+            save_lines(&save),
+            (String::from("HI"), summary.trim().to_string())
+        );
+    }
+
+    #[test]
+    fn summary_without_name() {
+        let save = named("  ");
+        let summary = save.summary();
+        let (line1, line2) = save_lines(&save);
+        assert_eq!(line1, summary[..SUMMARY_SPLIT].trim());
+        assert!(summary[SUMMARY_SPLIT..].trim().starts_with(&line2));
+    }
+
+    #[test]
+    fn multibyte_name_cut_by_character() {
+        let (line1, _) = save_lines(&named("ジャンプのサウンドエフェクトですジャンプのサウンド"));
+        assert_eq!(line1, "ジャンプのサウンドエフェクトですジャン");
+        assert_eq!(line1.chars().count(), SAVE_LINE_CHARS);
+        let (line1, _) = save_lines(&named(&"é".repeat(30)));
+        assert_eq!(line1, "É".repeat(SAVE_LINE_CHARS));
+    }
+
+    /// Only checks drawing doesn't panic, slicing by bytes used to
+    #[test]
+    fn draws_multibyte_name_without_panicking() {
+        let mut buffer = Graphics::create_buffer_u8(120, 24);
+        let mut graphics = Graphics::new_u8_rgba(&mut buffer, 120, 24).unwrap();
+        draw_save(&mut graphics, &themes()[0], 1, &named("ジャンプのサウンド"));
+    }
 }
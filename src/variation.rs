@@ -0,0 +1,227 @@
+use crate::export::{sanitise_name, write_audio, ExportOptions};
+use crate::settings::SoundSave;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Seconds of silence between variants when previewing
+pub const PREVIEW_GAP: f32 = 0.2;
+
+/// How much each parameter may vary by, as a fraction of its value, so 0.1 is ±10%
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Jitter {
+    pub volume: f32,
+    pub attack: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub release: f32,
+    pub freq: f32,
+    pub crunch: f32,
+    pub drive: f32,
+}
+
+impl Default for Jitter {
+    fn default() -> Self {
+        Jitter {
+            volume: 0.1,
+            attack: 0.1,
+            decay: 0.1,
+            sustain: 0.0,
+            release: 0.1,
+            freq: 0.05,
+            crunch: 0.0,
+            drive: 0.0,
+        }
+    }
+}
+
+impl Jitter {
+    /// Set the range of a parameter by name, such as `freq`
+    pub fn set(&mut self, name: &str, range: f32) -> Result<()> {
+        let value = match name {
+            "volume" => &mut self.volume,
+            "attack" => &mut self.attack,
+            "decay" => &mut self.decay,
+            "sustain" => &mut self.sustain,
+            "release" => &mut self.release,
+            "freq" => &mut self.freq,
+            "crunch" => &mut self.crunch,
+            "drive" => &mut self.drive,
+            _ => return Err(anyhow!("Unknown parameter {name}")),
+        };
+        *value = range.abs();
+        Ok(())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VariationSettings {
+    pub count: usize,
+    pub seed: u64,
+    pub jitter: Jitter,
+}
+
+impl Default for VariationSettings {
+    fn default() -> Self {
+        VariationSettings {
+            count: 8,
+            seed: 1,
+            jitter: Jitter::default(),
+        }
+    }
+}
+
+/// Parameter sets written next to the exported variants
+#[derive(Debug, Serialize)]
+struct VariationFile<'a> {
+    settings: &'a VariationSettings,
+    variants: &'a [SoundSave],
+}
+
+/// SplitMix64, so the same seed always gives the same variants
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut value = self.0;
+        value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        value ^ (value >> 31)
+    }
+
+    /// Random multiplier between 1 - `range` and 1 + `range`
+    fn scale(&mut self, range: f32) -> f32 {
+        let unit = (self.next() >> 40) as f32 / (1_u64 << 24) as f32;
+        1.0 + range * (unit * 2.0 - 1.0)
+    }
+}
+
+/// Copies of `sound` with the parameters randomly changed within the ranges in `settings`
+pub fn create_variants(sound: &SoundSave, settings: &VariationSettings) -> Vec<SoundSave> {
+    let mut rng = Rng(settings.seed);
+    let jitter = &settings.jitter;
+    (0..settings.count)
+        .map(|_| {
+            let mut variant = sound.clone();
+            variant.volume = (variant.volume * rng.scale(jitter.volume)).max(0.0);
+            variant.attack = (variant.attack * rng.scale(jitter.attack)).max(0.0);
+            variant.decay = (variant.decay * rng.scale(jitter.decay)).max(0.0);
+            variant.sustain = (variant.sustain * rng.scale(jitter.sustain)).clamp(0.0, 1.0);
+            variant.release = (variant.release * rng.scale(jitter.release)).max(0.0);
+            variant.freq = (variant.freq as f32 * rng.scale(jitter.freq))
+                .round()
+                .max(1.0) as u64;
+            variant.crunch = (variant.crunch * rng.scale(jitter.crunch)).max(0.0);
+            variant.drive = (variant.drive * rng.scale(jitter.drive)).max(0.0);
+            variant
+        })
+        .collect()
+}
+
+/// All variants one after another, separated by [PREVIEW_GAP] seconds of silence
+pub fn preview(variants: &[SoundSave], sample_rate: u32, max_length: f32) -> Vec<f32> {
+    let gap = vec![0.0; (PREVIEW_GAP * sample_rate as f32) as usize];
+    let mut output = vec![];
+    for variant in variants {
+        output.extend(variant.create_data(max_length));
+        output.extend_from_slice(&gap);
+    }
+    output
+}
+
+/// Write each variant (up to `max_length` seconds) to a numbered file in `dir` along with a JSON
+/// file of the parameters used, returns the path of the JSON file
+pub fn export_variants(
+    sound: &SoundSave,
+    variants: &[SoundSave],
+    settings: &VariationSettings,
+    options: &ExportOptions,
+    max_length: f32,
+    dir: &Path,
+) -> Result<PathBuf> {
+    options.check_audio()?;
+    fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    let name = sanitise_name(&sound.display_name());
+    for (i, variant) in variants.iter().enumerate() {
+        let path = dir.join(format!(
+            "{name}_{:02}.{}",
+            i + 1,
+            options.format.extension()
+        ));
+        write_audio(&path, &variant.create_data(max_length), options)?;
+    }
+    let json = serde_json::to_string_pretty(&VariationFile { settings, variants })?;
+    let path = dir.join(format!("{name}_variants.json"));
+    fs::write(&path, json).with_context(|| format!("writing {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sound() -> SoundSave {
+        let mut sound = SoundSave::new_blank();
+        sound.volume = 1.0;
+        sound.attack = 0.1;
+        sound.decay = 0.2;
+        sound.sustain = 0.5;
+        sound.release = 0.4;
+        sound.freq = 500;
+        sound.crunch = 0.3;
+        sound.drive = 0.6;
+        sound
+    }
+
+    fn settings(seed: u64) -> VariationSettings {
+        VariationSettings {
+            count: 100,
+            seed,
+            jitter: Jitter {
+                volume: 0.1,
+                attack: 0.2,
+                decay: 0.3,
+                sustain: 0.0,
+                release: 0.4,
+                freq: 0.05,
+                crunch: 0.5,
+                drive: 1.0,
+            },
+        }
+    }
+
+    #[test]
+    fn same_seed_same_variants() {
+        let sound = sound();
+        let variants = create_variants(&sound, &settings(7));
+        assert_eq!(variants.len(), 100);
+        assert_eq!(variants, create_variants(&sound, &settings(7)));
+        assert_ne!(variants, create_variants(&sound, &settings(8)));
+    }
+
+    #[test]
+    fn jitter_within_range() {
+        let sound = sound();
+        let settings = settings(3);
+        let jitter = settings.jitter;
+        let within = |value: f32, base: f32, range: f32| {
+            let margin = base * range + 0.0001;
+            value >= base - margin && value <= base + margin
+        };
+        for variant in create_variants(&sound, &settings) {
+            assert!(within(variant.volume, sound.volume, jitter.volume));
+            assert!(within(variant.attack, sound.attack, jitter.attack));
+            assert!(within(variant.decay, sound.decay, jitter.decay));
+            assert_eq!(variant.sustain, sound.sustain);
+            assert!(within(variant.release, sound.release, jitter.release));
+            assert!(within(variant.freq as f32, sound.freq as f32, jitter.freq));
+            assert!(within(variant.crunch, sound.crunch, jitter.crunch));
+            assert!(within(variant.drive, sound.drive, jitter.drive));
+            assert!(variant.drive >= 0.0);
+        }
+    }
+}
@@ -1,30 +1,81 @@
 use pixels_graphics_lib::prelude::PixelFont::Standard4x4;
 use pixels_graphics_lib::prelude::*;
 
+type Line = (Coord, Coord);
+
+/// Values drawn as gridlines in absolute mode
+const GRIDLINES: [f32; 5] = [-1.0, -0.5, 0.0, 0.5, 1.0];
+
+/// If `absolute` is true the waveform is drawn on a fixed ±1.0 scale, otherwise it's stretched to
+/// fit the sound's own min and max
 #[derive(Debug, Clone)]
 pub struct Waveform {
     pub duration: f32,
-    points: Vec<(Coord, Coord)>,
+    /// Number of samples outside ±1.0
+    pub clipped: usize,
+    pub absolute: bool,
+    points: Vec<Line>,
+    /// Points of the columns that contain clipped samples
+    clipped_points: Vec<Line>,
+    grid: Vec<Line>,
     error: bool,
     center: TextPos,
 }
 
 impl Waveform {
-    pub fn new(data: Vec<f32>, sample_rate: usize, width: usize, height: usize) -> Self {
+    pub fn new(
+        data: Vec<f32>,
+        sample_rate: usize,
+        width: usize,
+        height: usize,
+        absolute: bool,
+    ) -> Self {
         let duration = data.len() as f32 / sample_rate as f32;
-        let (error, points) = if data.iter().any(|v| v.is_nan() || v.is_infinite()) {
-            (true, vec![])
+        let clipped = data.iter().filter(|v| v.abs() > 1.0).count();
+        let (error, points, clipped_points) = if data.iter().any(|v| v.is_nan() || v.is_infinite())
+        {
+            (true, vec![], vec![])
         } else {
-            (false, to_waveform(data, width, height))
+            let (points, clipped_points) = to_waveform(data, width, height, absolute);
+            (false, points, clipped_points)
+        };
+        let grid = if absolute {
+            GRIDLINES
+                .iter()
+                .map(|value| {
+                    let y = to_y(-1.0, 1.0, *value, height);
+                    (coord!(0, y), coord!(width, y))
+                })
+                .collect()
+        } else {
+            vec![]
         };
         Waveform {
             duration,
+            clipped,
+            absolute,
             points,
+            clipped_points,
+            grid,
             error,
             center: TextPos::Px((width / 2) as isize, (height / 2) as isize),
         }
     }
 
+    /// Draw the ±1.0, ±0.5 and 0 lines, only in absolute mode
+    pub fn render_grid(&self, graphics: &mut Graphics, color: Color) {
+        for (start, end) in &self.grid {
+            graphics.draw_line(start, end, color);
+        }
+    }
+
+    /// Redraw the parts of the waveform that are clipping
+    pub fn render_clipping(&self, graphics: &mut Graphics, color: Color) {
+        for (top, bottom) in &self.clipped_points {
+            graphics.draw_line(top, bottom, color);
+        }
+    }
+
     pub fn render_line(&self, graphics: &mut Graphics, color: Color) {
         if self.error {
             graphics.draw_text(
@@ -39,33 +90,44 @@ impl Waveform {
     }
 }
 
-fn to_waveform(data: Vec<f32>, width: usize, height: usize) -> Vec<(Coord, Coord)> {
+/// Line for each column of the waveform, and the lines for just the columns that clip
+fn to_waveform(
+    data: Vec<f32>,
+    width: usize,
+    height: usize,
+    absolute: bool,
+) -> (Vec<Line>, Vec<Line>) {
     if data.is_empty() {
-        return vec![];
+        return (vec![], vec![]);
     }
     let chunk_len = data.len() / width;
-    let (total_min, total_max) = min_max(&data);
+    let (total_min, total_max) = if absolute {
+        (-1.0, 1.0)
+    } else {
+        min_max(&data)
+    };
     if chunk_len == 0 {
-        return vec![];
+        return (vec![], vec![]);
     }
 
-    data.chunks_exact(chunk_len)
-        .map(min_max)
-        .map(|(min, max)| {
-            (
-                inv_flerp(total_min, total_max, min),
-                inv_flerp(total_min, total_max, max),
-            )
-        })
-        .map(|(min, max)| {
-            (
-                (min * height as f32) as usize,
-                (max * height as f32) as usize,
-            )
-        })
-        .enumerate()
-        .map(|(x, (min, max))| (coord!(x, min), coord!(x, max)))
-        .collect()
+    let mut points = vec![];
+    let mut clipped = vec![];
+    for (x, chunk) in data.chunks_exact(chunk_len).enumerate() {
+        let (min, max) = min_max(chunk);
+        let line = (
+            coord!(x, to_y(total_min, total_max, min, height)),
+            coord!(x, to_y(total_min, total_max, max, height)),
+        );
+        if min < -1.0 || max > 1.0 {
+            clipped.push(line);
+        }
+        points.push(line);
+    }
+    (points, clipped)
+}
+
+fn to_y(min: f32, max: f32, value: f32, height: usize) -> usize {
+    (inv_flerp(min, max, value).clamp(0.0, 1.0) * height as f32) as usize
 }
 
 fn min_max(nums: &[f32]) -> (f32, f32) {
@@ -0,0 +1,145 @@
+use crate::audio::SAMPLE_RATE;
+use crate::meter::Levels;
+use crate::settings::SoundSave;
+use crate::waveform::Waveform;
+use anyhow::{anyhow, Result};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+const WAVEFORM_WIDTH: usize = 334;
+const WAVEFORM_HEIGHT: usize = 42;
+
+/// Sound to render, `compare` is only set if its waveform needs rendering too
+///
+/// `max_length` is in seconds
+struct Job {
+    id: u64,
+    sound: SoundSave,
+    compare: Option<SoundSave>,
+    absolute: bool,
+    max_length: f32,
+}
+
+pub struct Rendered {
+    id: u64,
+    pub waveform: Waveform,
+    pub levels: Levels,
+    pub compare: Option<Waveform>,
+}
+
+impl Job {
+    /// Render the job, None if a newer one was requested before it finished
+    fn run(self, latest: &AtomicU64) -> Option<Rendered> {
+        let cancelled = || latest.load(Ordering::Relaxed) != self.id;
+        let data = self.sound.create_data_unless(self.max_length, &cancelled)?;
+        let levels = Levels::measure(&data, SAMPLE_RATE);
+        let waveform = |data| {
+            Waveform::new(
+                data,
+                SAMPLE_RATE as usize,
+                WAVEFORM_WIDTH,
+                WAVEFORM_HEIGHT,
+                self.absolute,
+            )
+        };
+        let compare = match &self.compare {
+            Some(compare) => Some(waveform(
+                compare.create_data_unless(self.max_length, &cancelled)?,
+            )),
+            None => None,
+        };
+        Some(Rendered {
+            id: self.id,
+            levels,
+            compare,
+            waveform: waveform(data),
+        })
+    }
+}
+
+/// Renders waveforms and levels on a separate thread so long sounds don't freeze the UI
+///
+/// Only the newest request matters, any older ones still waiting are skipped and one being
+/// rendered is stopped
+///
+/// If the thread stops (a render panicked) it's restarted, the request it was working on is lost
+pub struct WaveformWorker {
+    latest: Arc<AtomicU64>,
+    jobs: Sender<Job>,
+    results: Receiver<Rendered>,
+}
+
+impl WaveformWorker {
+    pub fn new() -> Self {
+        let latest = Arc::new(AtomicU64::new(0));
+        let (jobs, job_receiver) = channel::<Job>();
+        let (result_sender, results) = channel();
+        let worker_latest = latest.clone();
+        thread::spawn(move || {
+            while let Ok(mut job) = job_receiver.recv() {
+                while let Ok(newer) = job_receiver.try_recv() {
+                    job = newer;
+                }
+                let Some(rendered) = job.run(&worker_latest) else {
+                    continue;
+                };
+                if rendered.id == worker_latest.load(Ordering::Relaxed)
+                    && result_sender.send(rendered).is_err()
+                {
+                    break;
+                }
+            }
+        });
+        WaveformWorker {
+            latest,
+            jobs,
+            results,
+        }
+    }
+
+    /// Start rendering `sound`, replacing any previous request
+    pub fn request(
+        &mut self,
+        sound: SoundSave,
+        compare: Option<SoundSave>,
+        absolute: bool,
+        max_length: f32,
+    ) {
+        let id = self.latest.fetch_add(1, Ordering::Relaxed) + 1;
+        let job = Job {
+            id,
+            sound,
+            compare,
+            absolute,
+            max_length,
+        };
+        if let Err(err) = self.jobs.send(job) {
+            eprintln!("waveform worker has stopped, restarting");
+            *self = WaveformWorker::new();
+            self.latest.store(id, Ordering::Relaxed);
+            let _ = self.jobs.send(err.0);
+        }
+    }
+
+    /// The result of the newest request, if it's finished
+    ///
+    /// Returns an error if the worker stopped, it's restarted but the request needs repeating
+    pub fn poll(&mut self) -> Result<Option<Rendered>> {
+        let latest = self.latest.load(Ordering::Relaxed);
+        let mut newest = None;
+        loop {
+            match self.results.try_recv() {
+                Ok(rendered) if rendered.id == latest => newest = Some(rendered),
+                Ok(_) => {}
+                Err(TryRecvError::Empty) => return Ok(newest),
+                Err(TryRecvError::Disconnected) => {
+                    *self = WaveformWorker::new();
+                    self.latest.store(latest, Ordering::Relaxed);
+                    return Err(anyhow!("Waveform worker stopped"));
+                }
+            }
+        }
+    }
+}